@@ -19,6 +19,93 @@ pub struct AppConfig {
     pub path_max_len: u32,
     #[serde(default = "default_auto_scan_enabled")]
     pub auto_scan_enabled: bool,
+    // 主索引的自动扫描频率（取值同 IndexCollection::schedule）
+    #[serde(default = "default_schedule")]
+    pub scan_schedule: String,
+    // 扫描时遵循 .gitignore / .ignore / .searcheveryignore（默认关闭）
+    #[serde(default)]
+    pub respect_ignore_files: bool,
+    // 扫描不跨越挂载点（避免从 / 进入网络盘、光驱等）
+    #[serde(default = "default_one_file_system")]
+    pub one_file_system: bool,
+    // 跳过的挂载分类（Linux）："pseudo" | "tmpfs" | "overlay" | "fuse" | "network"
+    #[serde(default = "crate::mounts::default_skip_kinds")]
+    pub skip_mount_kinds: Vec<String>,
+    // 额外的命名索引集合（各自的扫描根目录、索引目录与扫描频率）；上面的主索引视为名为 "default" 的集合
+    #[serde(default)]
+    pub collections: Vec<IndexCollection>,
+}
+
+// 主索引（index_dir / scan_roots）对应的集合名称
+pub const DEFAULT_COLLECTION: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexCollection {
+    pub name: String,
+    pub index_dir: String,
+    #[serde(default)]
+    pub scan_roots: Vec<String>,
+    // 为空时沿用全局排除规则
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    // 自动扫描频率："daily"（每天一次，系统空闲时）| "hourly" | "manual"（仅手动触发）
+    #[serde(default = "default_schedule")]
+    pub schedule: String,
+    #[serde(default = "default_collection_enabled")]
+    pub enabled: bool,
+}
+
+impl AppConfig {
+    // 全部启用的集合，主索引在前
+    pub fn all_collections(&self) -> Vec<IndexCollection> {
+        let main = IndexCollection {
+            name: DEFAULT_COLLECTION.into(),
+            index_dir: self.index_dir.clone(),
+            scan_roots: self.scan_roots.clone(),
+            exclude_patterns: Vec::new(),
+            schedule: self.scan_schedule.clone(),
+            enabled: true,
+        };
+        std::iter::once(main).chain(self.collections.iter().filter(|c| c.enabled).cloned()).collect()
+    }
+
+    // 集合名称须非空且互不相同，"default" 保留给主索引
+    pub fn validate(&self) -> Result<()> {
+        let mut names: Vec<&str> = Vec::new();
+        for c in &self.collections {
+            let name = c.name.trim();
+            if name.is_empty() { anyhow::bail!("索引集合名称不能为空"); }
+            if name.eq_ignore_ascii_case(DEFAULT_COLLECTION) { anyhow::bail!("索引集合名称 \"{}\" 保留给主索引", name); }
+            if names.contains(&name) { anyhow::bail!("索引集合名称重复：{}", name); }
+            names.push(name);
+        }
+        Ok(())
+    }
+
+    pub fn collection(&self, name: &str) -> Option<IndexCollection> {
+        self.all_collections().into_iter().find(|c| c.name == name)
+    }
+
+    // 集合的扫描参数：根目录与排除规则取集合自身设置，其余沿用全局
+    pub fn collection_scan_options(&self, c: &IndexCollection) -> crate::ScanOptions {
+        let mut opts = self.scan_options();
+        opts.roots = c.scan_roots.clone();
+        if !c.exclude_patterns.is_empty() { opts.exclude_patterns = c.exclude_patterns.clone(); }
+        opts
+    }
+
+    // 由配置生成后台扫描参数（自动扫描与手动触发共用）
+    pub fn scan_options(&self) -> crate::ScanOptions {
+        crate::ScanOptions {
+            roots: self.scan_roots.clone(),
+            exclude_patterns: self.exclude_patterns.clone(),
+            max_file_size_mb: Some(500),
+            follow_symlinks: false,
+            respect_ignore_files: self.respect_ignore_files,
+            one_file_system: self.one_file_system,
+            skip_mount_kinds: self.skip_mount_kinds.clone(),
+        }
+    }
 }
 
 fn config_path() -> Result<std::path::PathBuf> {
@@ -38,6 +125,11 @@ pub async fn read_config() -> Result<AppConfig> {
             index_dir: default_index_dir(),
             path_max_len: default_path_max_len(),
             auto_scan_enabled: default_auto_scan_enabled(),
+            scan_schedule: default_schedule(),
+            respect_ignore_files: false,
+            one_file_system: default_one_file_system(),
+            skip_mount_kinds: crate::mounts::default_skip_kinds(),
+            collections: Vec::new(),
         };
         write_config(&default).await?;
         return Ok(default);
@@ -52,6 +144,7 @@ pub async fn read_config() -> Result<AppConfig> {
     if cfg.exclude_patterns.is_empty() { cfg.exclude_patterns = default_exclude_patterns(); }
     if cfg.index_dir.trim().is_empty() { cfg.index_dir = default_index_dir(); }
     if cfg.path_max_len == 0 { cfg.path_max_len = default_path_max_len(); }
+    if cfg.scan_schedule.trim().is_empty() { cfg.scan_schedule = default_schedule(); }
 
     // 开发模式防御：若 index_dir 位于 src-tauri 目录内，改写为默认目录，避免开发重建循环
     if cfg.index_dir.to_lowercase().contains("src-tauri") {
@@ -62,6 +155,7 @@ pub async fn read_config() -> Result<AppConfig> {
 }
 
 pub async fn write_config(cfg: &AppConfig) -> Result<()> {
+    cfg.validate()?;
     let p = config_path()?;
     // 写入前防御：避免将 index_dir 设为 src-tauri 下路径
     let mut cfg_fixed = cfg.clone();
@@ -69,6 +163,13 @@ pub async fn write_config(cfg: &AppConfig) -> Result<()> {
         warn!("write_config: index_dir is under src-tauri, redirecting to default to avoid dev rebuilds");
         cfg_fixed.index_dir = default_index_dir();
     }
+    // index_dir 变更（或集合被移除）时释放旧目录的缓存索引（关闭文件句柄，之后检索按新目录重新打开）
+    if let Some(prev) = fs::read_to_string(&p).ok().and_then(|s| serde_json::from_str::<AppConfig>(&s).ok()) {
+        let current: Vec<String> = cfg_fixed.all_collections().into_iter().map(|c| c.index_dir).collect();
+        for c in prev.all_collections() {
+            if !current.contains(&c.index_dir) { crate::indexer::invalidate_cached(&c.index_dir); }
+        }
+    }
     let s = serde_json::to_string_pretty(&cfg_fixed)?;
     fs::write(&p, s)?;
     info!("config written to {:?}", p);
@@ -79,6 +180,9 @@ pub async fn write_config(cfg: &AppConfig) -> Result<()> {
 pub async fn reset_config() -> Result<AppConfig> {
     let p = config_path()?;
     if p.exists() {
+        if let Some(prev) = fs::read_to_string(&p).ok().and_then(|s| serde_json::from_str::<AppConfig>(&s).ok()) {
+            for c in prev.all_collections() { crate::indexer::invalidate_cached(&c.index_dir); }
+        }
         // 尝试删除旧配置文件；忽略删除失败以保证流程继续
         let _ = fs::remove_file(&p);
         warn!("old config removed: {:?}", p);
@@ -100,25 +204,57 @@ fn default_scan_roots() -> Vec<String> {
         }
         if drives.is_empty() { vec!["C:/".into()] } else { drives }
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
+    {
+        // 根目录 + 全部本地磁盘挂载点（配合 one_file_system 时需单独列出，如 /home、/data）
+        let mut roots = vec!["/".to_string()];
+        roots.extend(crate::mounts::local_disk_mounts());
+        roots
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         let home = tauri::api::path::home_dir().unwrap_or_else(|| std::path::PathBuf::from("~/"));
         vec![home.to_string_lossy().to_string()]
     }
 }
 
+// 按平台区分的默认排除规则；绝对路径按前缀匹配（见 scanner::is_excluded）
 fn default_exclude_patterns() -> Vec<String> {
-    vec![
-        "\\Windows".into(),
-        "\\Program Files".into(),
-        "\\Program Files (x86)".into(),
-        "\\AppData".into(),
-        "\\ProgramData".into(),
-        "\\Temp".into(),
-        "\\$Recycle.Bin".into(),
-        "\\System Volume Information".into(),
-        "\\node_modules".into(),
-    ]
+    #[cfg(target_os = "windows")]
+    {
+        vec![
+            "\\Windows".into(),
+            "\\Program Files".into(),
+            "\\Program Files (x86)".into(),
+            "\\AppData".into(),
+            "\\ProgramData".into(),
+            "\\Temp".into(),
+            "\\$Recycle.Bin".into(),
+            "\\System Volume Information".into(),
+            "\\node_modules".into(),
+        ]
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut v: Vec<String> = vec!["/System".into(), "/Library".into(), "/private/var".into(), "/Volumes/Recovery".into()];
+        if let Some(home) = tauri::api::path::home_dir() {
+            v.push(home.join("Library").to_string_lossy().to_string());
+        }
+        v.push("node_modules".into());
+        v
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        vec![
+            "/proc".into(),
+            "/sys".into(),
+            "/dev".into(),
+            "/run".into(),
+            "/var/log".into(),
+            "/lost+found".into(),
+            "node_modules".into(),
+        ]
+    }
 }
 
 fn default_index_dir() -> String {
@@ -128,4 +264,36 @@ fn default_index_dir() -> String {
 
 fn default_path_max_len() -> u32 { 80 }
 
-fn default_auto_scan_enabled() -> bool { true }
\ No newline at end of file
+fn default_auto_scan_enabled() -> bool { true }
+
+fn default_one_file_system() -> bool { true }
+
+fn default_schedule() -> String { "daily".into() }
+
+fn default_collection_enabled() -> bool { true }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(collections: serde_json::Value) -> AppConfig {
+        serde_json::from_value(serde_json::json!({ "collections": collections })).unwrap()
+    }
+
+    #[test]
+    fn main_collection_uses_configured_schedule() {
+        let mut cfg = config(serde_json::json!([{ "name": "work", "index_dir": "/idx/work", "schedule": "hourly" }]));
+        assert_eq!(cfg.collection(DEFAULT_COLLECTION).unwrap().schedule, "daily");
+        cfg.scan_schedule = "manual".into();
+        let schedules: Vec<String> = cfg.all_collections().into_iter().map(|c| c.schedule).collect();
+        assert_eq!(schedules, ["manual", "hourly"]);
+    }
+
+    #[test]
+    fn rejects_reserved_empty_and_duplicate_collection_names() {
+        let named = |names: &[&str]| config(names.iter().map(|n| serde_json::json!({ "name": n, "index_dir": format!("/idx/{}", n) })).collect());
+        assert!(named(&["work", "photos"]).validate().is_ok());
+        assert!(named(&["work", "Default"]).validate().is_err());
+        assert!(named(&["work", " "]).validate().is_err());
+        assert!(named(&["work", "photos", "work "]).validate().is_err());
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::{fs::File, io::Read, path::Path};
+use std::{ffi::OsString, fs::File, io::Read, path::{Path, PathBuf}};
 use log::info;
 
+use crate::pathenc;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DupGroup {
     // kind: "hash" 或 "name"
@@ -11,39 +13,59 @@ pub struct DupGroup {
     // key: 若 kind=hash 则为内容哈希；若 kind=name 则为文件名
     pub key: String,
     pub files: Vec<String>,
+    // 与 files 一一对应：非 UTF-8 路径的原始字节（十六进制），其余为 None
+    pub raw_paths: Vec<Option<String>>,
 }
 
-pub async fn detect(paths: Vec<String>) -> Result<Vec<DupGroup>> {
+// raw_paths 与 paths 按下标对应（可为空或短于 paths），用于还原非 UTF-8 路径
+pub async fn detect(paths: Vec<String>, raw_paths: Vec<Option<String>>) -> Result<Vec<DupGroup>> {
     use std::collections::HashMap;
     info!("dedup::detect start: paths={}", paths.len());
-    let mut hash_map: HashMap<String, Vec<String>> = HashMap::new();
-    let mut name_map: HashMap<String, Vec<String>> = HashMap::new();
+    let entries: Vec<(String, Option<String>, PathBuf)> = paths
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let raw = raw_paths.get(i).cloned().flatten();
+            let real = pathenc::resolve(&p, raw.as_deref());
+            (p, raw, real)
+        })
+        .collect();
+    let mut hash_map: HashMap<String, Vec<usize>> = HashMap::new();
+    // 按原始文件名分组，避免不同的非 UTF-8 名称因有损转换而误判为同名
+    let mut name_map: HashMap<OsString, Vec<usize>> = HashMap::new();
 
-    for p in &paths {
-        // 名称分组（不含路径）
-        if let Some(name) = std::path::Path::new(p).file_name().and_then(|s| s.to_str()) {
-            name_map.entry(name.to_string()).or_default().push(p.clone());
+    for (i, (_, _, real)) in entries.iter().enumerate() {
+        // 名称分组（不含路径）；文件夹不参与重复检测
+        if real.is_dir() { continue; }
+        if let Some(name) = real.file_name() {
+            name_map.entry(name.to_os_string()).or_default().push(i);
         }
     }
 
     // 计算内容哈希（可能耗时，放最后）
-    for p in paths {
-        if let Some(h) = hash_file(&p)? {
-            hash_map.entry(h).or_default().push(p);
+    for (i, (_, _, real)) in entries.iter().enumerate() {
+        if let Some(h) = hash_file(real)? {
+            hash_map.entry(h).or_default().push(i);
         }
     }
 
+    let group = |kind: &str, key: String, idx: Vec<usize>| DupGroup {
+        kind: kind.to_string(),
+        key,
+        files: idx.iter().map(|&i| entries[i].0.clone()).collect(),
+        raw_paths: idx.iter().map(|&i| entries[i].1.clone()).collect(),
+    };
     let mut groups: Vec<DupGroup> = Vec::new();
     // hash 组
-    for (h, files) in hash_map.into_iter() {
-        if files.len() > 1 {
-            groups.push(DupGroup { kind: "hash".to_string(), key: h, files });
+    for (h, idx) in hash_map.into_iter() {
+        if idx.len() > 1 {
+            groups.push(group("hash", h, idx));
         }
     }
     // name 组
-    for (name, files) in name_map.into_iter() {
-        if files.len() > 1 {
-            groups.push(DupGroup { kind: "name".to_string(), key: name, files });
+    for (name, idx) in name_map.into_iter() {
+        if idx.len() > 1 {
+            groups.push(group("name", name.to_string_lossy().to_string(), idx));
         }
     }
 
@@ -51,8 +73,7 @@ pub async fn detect(paths: Vec<String>) -> Result<Vec<DupGroup>> {
     Ok(groups)
 }
 
-fn hash_file(path: &str) -> Result<Option<String>> {
-    let p = Path::new(path);
+fn hash_file(p: &Path) -> Result<Option<String>> {
     if !p.exists() || !p.is_file() {
         return Ok(None);
     }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use sysinfo::System;
 use log::info;
 
-use crate::{config, pipeline_state};
+use crate::{config, mounts, pipeline_state, scanner};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DiagnosticsReport {
@@ -18,9 +18,22 @@ pub struct DiagnosticsReport {
     pub sys_cpu_avg: Option<f32>,
     pub sys_total_mem_kib: Option<u64>,
     pub sys_free_mem_kib: Option<u64>,
+    pub config_respect_ignore_files: bool,
+    // 最近一次扫描统计（含各忽略规则来源排除的路径数）；本次启动尚未扫描时为空
+    pub last_scan: Option<scanner::ScanStats>,
+    pub config_one_file_system: bool,
+    // 当前挂载点及其分类（仅 Linux），skipped 表示按配置会被扫描跳过
+    pub mounts: Vec<MountReport>,
     pub warnings: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MountReport {
+    #[serde(flatten)]
+    pub mount: mounts::MountEntry,
+    pub skipped: bool,
+}
+
 #[tauri::command]
 pub async fn diagnostics_report() -> Result<DiagnosticsReport, String> {
     use tantivy::{Index, query::AllQuery, collector::Count};
@@ -96,6 +109,16 @@ pub async fn diagnostics_report() -> Result<DiagnosticsReport, String> {
         sys_cpu_avg,
         sys_total_mem_kib,
         sys_free_mem_kib,
+        config_respect_ignore_files: cfg.respect_ignore_files,
+        last_scan: scanner::last_stats(),
+        config_one_file_system: cfg.one_file_system,
+        mounts: mounts::list_mounts()
+            .into_iter()
+            .map(|m| {
+                let skipped = m.mount_point != "/" && cfg.skip_mount_kinds.iter().any(|k| k.eq_ignore_ascii_case(&m.kind));
+                MountReport { mount: m, skipped }
+            })
+            .collect(),
         warnings,
     };
     info!("diagnostics_report generated");
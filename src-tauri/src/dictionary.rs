@@ -0,0 +1,304 @@
+use anyhow::Result;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tantivy::query::{BooleanQuery, Occur, PhraseQuery, Query, TermQuery};
+use tantivy::schema::IndexRecordOption;
+use tantivy::Term;
+
+use crate::config;
+use crate::indexer::{self, IndexFields};
+use crate::tokenizer;
+
+// 用户词典与同义词：用户词加入 jieba 分词词典（同时影响索引与查询的切分），
+// 同义词组在查询时展开（任一成员命中即可）。存放在应用配置目录的 dictionary.json
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserWord {
+    pub word: String,
+    // 词频与词性与 jieba 用户词典一致；未指定词频时按能切分出该词的最小词频
+    #[serde(default)]
+    pub freq: Option<usize>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    // 加入词典前的切分（转小写，多于一个词时记录）：此前写入的文档仍按此切分，
+    // 即使重写后也可能不含该词（如 "年度预算审查" 仍切为 年度预算/审查），查询时以短语形式一并匹配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dictionary {
+    #[serde(default)]
+    pub words: Vec<UserWord>,
+    // 同义词组（如 ["合同", "协议", "contract"]），成员已转小写
+    #[serde(default)]
+    pub synonyms: Vec<Vec<String>>,
+    // 尚未重写的索引：index_dir → 旧切分序列。索引正在写入（如扫描进行中）时重写推迟，
+    // 由该索引下一次管道运行结束后完成
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub pending: HashMap<String, Vec<Vec<String>>>,
+}
+
+// 用户词变更的结果：retokenized 为按新词典重写的文档数（切分未变化的词不触发重写）；
+// pending 为暂时无法写入、待下次扫描后重写的索引数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryUpdate {
+    pub words: usize,
+    pub retokenized: usize,
+    #[serde(default)]
+    pub pending: usize,
+}
+
+static DICT: Lazy<Mutex<Option<Dictionary>>> = Lazy::new(|| Mutex::new(None));
+
+fn dict_path() -> PathBuf {
+    tauri::api::path::app_config_dir(&tauri::Config::default())
+        .unwrap_or_else(|| PathBuf::from("./"))
+        .join("dictionary.json")
+}
+
+fn load() -> Dictionary {
+    let p = dict_path();
+    if !p.exists() { return Dictionary::default(); }
+    match fs::read_to_string(&p).map_err(anyhow::Error::from).and_then(|s| Ok(serde_json::from_str(&s)?)) {
+        Ok(d) => d,
+        Err(e) => {
+            warn!("dictionary: failed to load {:?}: {}", p, e);
+            Dictionary::default()
+        }
+    }
+}
+
+// 注意：闭包内不能触发分词（首次分词会初始化 jieba 并读取用户词，造成死锁）
+fn with_dict<R>(f: impl FnOnce(&mut Dictionary) -> Result<R>) -> Result<R> {
+    let mut guard = DICT.lock();
+    let d = guard.get_or_insert_with(load);
+    let r = f(d)?;
+    let p = dict_path();
+    if let Some(dir) = p.parent() { fs::create_dir_all(dir)?; }
+    fs::write(p, serde_json::to_string_pretty(d)?)?;
+    Ok(r)
+}
+
+pub fn get() -> Dictionary {
+    DICT.lock().get_or_insert_with(load).clone()
+}
+
+pub fn user_words() -> Vec<UserWord> {
+    DICT.lock().get_or_insert_with(load).words.clone()
+}
+
+// 用户词 word（已转小写的检索词）加入词典前的切分
+pub fn split_of(word: &str) -> Option<Vec<String>> {
+    let mut guard = DICT.lock();
+    let d = guard.get_or_insert_with(load);
+    d.words.iter().find(|w| w.word.to_lowercase() == word).and_then(|w| w.split.clone())
+}
+
+// ---------------- 同义词 ----------------
+
+// 替换全部同义词组：成员去空白、转小写并去重，少于两个成员的组丢弃
+pub fn set_synonyms(groups: Vec<Vec<String>>) -> Result<usize> {
+    let mut cleaned: Vec<Vec<String>> = Vec::new();
+    for g in groups {
+        let mut members: Vec<String> = Vec::new();
+        for m in g.iter().map(|m| m.trim().to_lowercase()).filter(|m| !m.is_empty()) {
+            if !members.contains(&m) { members.push(m); }
+        }
+        if members.len() >= 2 { cleaned.push(members); }
+    }
+    with_dict(|d| {
+        d.synonyms = cleaned;
+        Ok(d.synonyms.len())
+    })
+}
+
+// word 的同义词（不含自身）；出现在多个组中时取并集
+pub fn synonyms_of(word: &str) -> Vec<String> {
+    let lower = word.to_lowercase();
+    let mut guard = DICT.lock();
+    let d = guard.get_or_insert_with(load);
+    let mut out: Vec<String> = Vec::new();
+    for g in d.synonyms.iter().filter(|g| g.contains(&lower)) {
+        for m in g {
+            if *m != lower && !out.contains(m) { out.push(m.clone()); }
+        }
+    }
+    out
+}
+
+// ---------------- 用户词 ----------------
+
+// 解析 jieba 用户词典格式：每行 "词 [词频] [词性]"，空行与 # 开头的行忽略；第二列不是数字时视为词性
+pub fn parse_jieba(text: &str) -> Vec<UserWord> {
+    let mut out: Vec<UserWord> = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let mut parts = line.split_whitespace();
+        let Some(word) = parts.next() else { continue };
+        let (mut freq, mut tag) = (None, None);
+        if let Some(second) = parts.next() {
+            match second.parse::<usize>() {
+                Ok(n) => {
+                    freq = Some(n);
+                    tag = parts.next().map(str::to_string);
+                }
+                Err(_) => tag = Some(second.to_string()),
+            }
+        }
+        out.retain(|w: &UserWord| w.word != word);
+        out.push(UserWord { word: word.to_string(), freq, tag, split: None });
+    }
+    out
+}
+
+// 导出为 jieba 用户词典格式；只有词性时补上建议词频，使各列位置不变
+pub fn export_jieba() -> String {
+    let mut out = String::new();
+    for w in user_words() {
+        let line = match (w.freq, &w.tag) {
+            (Some(f), Some(t)) => format!("{} {} {}", w.word, f, t),
+            (Some(f), None) => format!("{} {}", w.word, f),
+            (None, Some(t)) => format!("{} {} {}", w.word, tokenizer::suggest_freq(&w.word), t),
+            (None, None) => w.word.clone(),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+// 添加（或更新词频与词性）用户词
+pub async fn add_words(words: Vec<UserWord>) -> Result<DictionaryUpdate> {
+    let words: Vec<UserWord> = words
+        .into_iter()
+        .map(|w| UserWord { word: w.word.trim().to_string(), ..w })
+        .filter(|w| !w.word.is_empty() && !w.word.contains(char::is_whitespace))
+        .collect();
+    if words.is_empty() { return Err(anyhow::anyhow!("词不能为空或包含空白")); }
+    update(words.iter().map(|w| w.word.clone()).collect(), |d| {
+        for w in words {
+            d.words.retain(|x| x.word != w.word);
+            d.words.push(w);
+        }
+    })
+    .await
+}
+
+pub async fn remove_words(words: Vec<String>) -> Result<DictionaryUpdate> {
+    update(words.clone(), |d| d.words.retain(|w| !words.contains(&w.word))).await
+}
+
+// 导入 jieba 格式的用户词典；replace 为 true 时替换现有的全部用户词
+pub async fn import_jieba(text: &str, replace: bool) -> Result<DictionaryUpdate> {
+    let words = parse_jieba(text);
+    if words.is_empty() && !replace { return Err(anyhow::anyhow!("未解析到任何词")); }
+    let mut changed: Vec<String> = words.iter().map(|w| w.word.clone()).collect();
+    if replace { changed.extend(user_words().into_iter().map(|w| w.word)); }
+    update(changed, |d| {
+        if replace { d.words.clear(); }
+        for w in words {
+            d.words.retain(|x| x.word != w.word);
+            d.words.push(w);
+        }
+    })
+    .await
+}
+
+// 修改用户词并重建分词词典；切分结果发生变化的词，重写包含该词（按旧切分的短语）的文档。
+// 词典修改即生效：重写所需的旧切分先记入 pending，某个索引暂时无法写入时保留，不作为错误返回
+async fn update(changed: Vec<String>, edit: impl FnOnce(&mut Dictionary)) -> Result<DictionaryUpdate> {
+    let cfg = config::read_config().await?;
+    let before: Vec<(String, Vec<String>)> = changed.iter().map(|w| (w.clone(), tokenizer::analyze(w))).collect();
+    let prev = user_words();
+    let words = with_dict(|d| {
+        edit(d);
+        record_splits(&mut d.words, &prev, &before);
+        Ok(d.words.clone())
+    })?;
+    tokenizer::reload_user_words(&words);
+    let stale: Vec<Vec<String>> = before
+        .into_iter()
+        .filter(|(w, old)| !old.is_empty() && tokenizer::analyze(w) != *old)
+        .map(|(_, old)| old)
+        .collect();
+    let dirs: Vec<String> = cfg.all_collections().into_iter().map(|c| c.index_dir).collect();
+    if !stale.is_empty() {
+        with_dict(|d| {
+            for dir in &dirs {
+                let pending = d.pending.entry(dir.clone()).or_default();
+                for old in &stale {
+                    if !pending.contains(old) { pending.push(old.clone()); }
+                }
+            }
+            Ok(())
+        })?;
+    }
+    let (mut retokenized, mut pending) = (0, 0);
+    for dir in &dirs {
+        match retokenize_pending(dir).await {
+            Ok(n) => retokenized += n,
+            Err(e) => {
+                warn!("dictionary: retokenize {} postponed: {}", dir, e);
+                pending += 1;
+            }
+        }
+    }
+    info!("dictionary: user words={}, changed={}, retokenized={}, pending={}", words.len(), changed.len(), retokenized, pending);
+    Ok(DictionaryUpdate { words: words.len(), retokenized, pending })
+}
+
+// 重写 index_dir 中按 pending 记录的旧切分包含用户词的文档，完成后清除记录；
+// 用户词变更时及管道释放 writer 后调用
+pub async fn retokenize_pending(index_dir: &str) -> Result<usize> {
+    let Some(stale) = DICT.lock().get_or_insert_with(load).pending.get(index_dir).cloned() else { return Ok(0) };
+    let open = indexer::open_cached(index_dir)?;
+    let query = stale_query(&open.fields, &stale);
+    let n = indexer::retokenize(index_dir, query.as_ref()).await?;
+    with_dict(|d| {
+        if let Some(p) = d.pending.get_mut(index_dir) { p.retain(|old| !stale.contains(old)); }
+        d.pending.retain(|_, p| !p.is_empty());
+        Ok(())
+    })?;
+    Ok(n)
+}
+
+// 记录用户词加入前的切分（before 为变更前的切分结果）；已在词典中的词（如仅修改词频）沿用原记录
+pub(crate) fn record_splits(words: &mut [UserWord], prev: &[UserWord], before: &[(String, Vec<String>)]) {
+    for w in words.iter_mut().filter(|w| w.split.is_none()) {
+        w.split = prev
+            .iter()
+            .find(|p| p.word == w.word)
+            .map(|p| p.split.clone())
+            .unwrap_or_else(|| before.iter().find(|(b, _)| *b == w.word).map(|(_, old)| old.clone()).filter(|old| old.len() > 1));
+    }
+}
+
+// 测试用：只替换内存中的词典并重建分词词典，不读写 dictionary.json
+#[cfg(test)]
+pub(crate) fn set_in_memory(d: Dictionary) {
+    let words = d.words.clone();
+    *DICT.lock() = Some(d);
+    tokenizer::reload_user_words(&words);
+}
+
+// 名称或正文中包含任一旧切分序列（按位置相邻）的文档
+fn stale_query(f: &IndexFields, stale: &[Vec<String>]) -> Box<dyn Query> {
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for old in stale {
+        for field in [f.name, f.content] {
+            let mut terms: Vec<Term> = old.iter().map(|w| Term::from_field_text(field, w)).collect();
+            let q: Box<dyn Query> = if terms.len() == 1 {
+                Box::new(TermQuery::new(terms.remove(0), IndexRecordOption::Basic))
+            } else {
+                Box::new(PhraseQuery::new(terms))
+            };
+            clauses.push((Occur::Should, q));
+        }
+    }
+    Box::new(BooleanQuery::new(clauses))
+}
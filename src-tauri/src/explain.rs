@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{DocId, Score, SegmentReader, Term};
+
+// 排序解释：把查询中各个来源（名称、正文、拼音、容错）的子查询包上标签，
+// explain 时按标签汇总得分贡献，得出命中字段
+
+// 标签在解释树中的描述前缀
+const LABEL_PREFIX: &str = "field:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldScore {
+    pub field: String,
+    // 该来源对总分的贡献（已乘权重）
+    pub score: f32,
+    pub boost: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    pub fields: Vec<FieldScore>,
+    // tantivy 原始解释树（BM25 的 idf、词频、字段长度等明细）
+    pub tree: serde_json::Value,
+}
+
+// 查询切分后实际检索的词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzedTerm {
+    pub field: String,
+    pub term: String,
+}
+
+// 带标签的查询：匹配与打分完全委托给内部查询，仅在 explain 时多出一层标注节点
+#[derive(Debug)]
+pub struct Labeled {
+    label: String,
+    inner: Box<dyn Query>,
+}
+
+impl Clone for Labeled {
+    fn clone(&self) -> Self {
+        Labeled { label: self.label.clone(), inner: self.inner.box_clone() }
+    }
+}
+
+pub fn labeled(label: impl Into<String>, inner: Box<dyn Query>) -> Box<dyn Query> {
+    Box::new(Labeled { label: label.into(), inner })
+}
+
+impl Query for Labeled {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(LabeledWeight { label: self.label.clone(), inner: self.inner.weight(enable_scoring)? }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.inner.query_terms(visitor);
+    }
+}
+
+struct LabeledWeight {
+    label: String,
+    inner: Box<dyn Weight>,
+}
+
+// 除 explain 外全部直接转发，保留内部 weight 的批量遍历与剪枝优化
+impl Weight for LabeledWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        self.inner.scorer(reader, boost)
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let inner = self.inner.explain(reader, doc)?;
+        let mut explanation = Explanation::new_with_string(format!("{}{}", LABEL_PREFIX, self.label), inner.value());
+        explanation.add_detail(inner);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> tantivy::Result<u32> {
+        self.inner.count(reader)
+    }
+
+    fn for_each(&self, reader: &SegmentReader, callback: &mut dyn FnMut(DocId, Score)) -> tantivy::Result<()> {
+        self.inner.for_each(reader, callback)
+    }
+
+    fn for_each_no_score(&self, reader: &SegmentReader, callback: &mut dyn FnMut(&[DocId])) -> tantivy::Result<()> {
+        self.inner.for_each_no_score(reader, callback)
+    }
+
+    fn for_each_pruning(&self, threshold: Score, reader: &SegmentReader, callback: &mut dyn FnMut(DocId, Score) -> Score) -> tantivy::Result<()> {
+        self.inner.for_each_pruning(threshold, reader, callback)
+    }
+}
+
+// 汇总解释树中各标签节点的得分（同一标签可出现多次，如多个词分别命中名称）；
+// boost_of 给出各标签的权重，仅用于展示
+pub fn summarize(explanation: &Explanation, boost_of: impl Fn(&str) -> f32) -> ScoreExplanation {
+    let tree = serde_json::to_value(explanation).unwrap_or_default();
+    let mut fields: Vec<FieldScore> = Vec::new();
+    collect(&tree, &mut |label, score| match fields.iter_mut().find(|f| f.field == label) {
+        Some(f) => f.score += score,
+        None => fields.push(FieldScore { field: label.to_string(), score, boost: boost_of(label) }),
+    });
+    fields.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ScoreExplanation { fields, tree }
+}
+
+// 乘在总分上的系数（如打开历史加权）：以 (系数 - 1) × 查询得分 计为单独的一项，使各项之和与返回的得分一致
+pub fn add_multiplier(explanation: &mut ScoreExplanation, label: &str, multiplier: f32) {
+    let base = explanation.tree.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    explanation.fields.push(FieldScore { field: label.to_string(), score: base * (multiplier - 1.0), boost: multiplier });
+    explanation.fields.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+fn collect(node: &serde_json::Value, visit: &mut dyn FnMut(&str, f32)) {
+    let description = node.get("description").and_then(|d| d.as_str()).unwrap_or("");
+    if let Some(label) = description.strip_prefix(LABEL_PREFIX) {
+        visit(label, node.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32);
+        return;
+    }
+    for child in node.get("details").and_then(|d| d.as_array()).into_iter().flatten() {
+        collect(child, visit);
+    }
+}
+
+// 列出查询中的词项（字段名 + 分词后的词）；模糊、正则等自动机查询不展开
+pub fn analyzed_terms(query: &dyn Query, schema: &tantivy::schema::Schema) -> Vec<AnalyzedTerm> {
+    let mut terms: Vec<AnalyzedTerm> = Vec::new();
+    query.query_terms(&mut |term, _| {
+        let value = term.value();
+        let Some(text) = value.as_str() else { return };
+        let field = schema.get_field_name(term.field()).to_string();
+        if !terms.iter().any(|t| t.field == field && t.term == text) {
+            terms.push(AnalyzedTerm { field, term: text.to_string() });
+        }
+    });
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplier_contribution_adds_up_to_the_returned_score() {
+        let mut root = Explanation::new("sum", 3.0);
+        for (label, value) in [("name", 2.0), ("content", 1.0)] {
+            let mut node = Explanation::new_with_string(format!("{}{}", LABEL_PREFIX, label), value);
+            node.add_detail(Explanation::new("bm25", value));
+            root.add_detail(node);
+        }
+        let mut e = summarize(&root, |_| 1.0);
+        add_multiplier(&mut e, "frecency", 1.5);
+        let fields: Vec<(&str, f32, f32)> = e.fields.iter().map(|f| (f.field.as_str(), f.score, f.boost)).collect();
+        assert_eq!(fields, [("name", 2.0, 1.0), ("frecency", 1.5, 1.5), ("content", 1.0, 1.0)]);
+        assert_eq!(e.fields.iter().map(|f| f.score).sum::<f32>(), 3.0 * 1.5);
+    }
+}
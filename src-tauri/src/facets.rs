@@ -0,0 +1,361 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::{Column, StrColumn};
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+// 分面统计：在全部命中文档上（而非当前页）按扩展名、大小区间、修改年份/月份与目录计数。
+// 均读取 fast 列，按列内序号计数，段结束时才把序号换成字符串
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: u64,
+    // 数值区间 [from, to)，用于点击后设置过滤条件（大小为字节，时间为 Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Facets {
+    pub ext: Vec<FacetCount>,
+    pub size: Vec<FacetCount>,
+    pub year: Vec<FacetCount>,
+    pub month: Vec<FacetCount>,
+    // dir 为 dir_base 的下一级目录；dir_base 取路径前缀过滤条件，未指定时取全部命中的最深公共目录
+    pub dir_base: String,
+    pub dir: Vec<FacetCount>,
+}
+
+// 每个分面最多返回的取值数量
+const MAX_VALUES: usize = 50;
+// 按月统计只返回最近的月份数
+const MAX_MONTHS: usize = 36;
+
+// 大小区间（与资源管理器的分组一致）：(名称, 下界, 上界)
+const SIZE_BUCKETS: &[(&str, u64, u64)] = &[
+    ("空 (0)", 0, 1),
+    ("极小 (< 16KB)", 1, 16 << 10),
+    ("小 (16KB - 1MB)", 16 << 10, 1 << 20),
+    ("中 (1MB - 128MB)", 1 << 20, 128 << 20),
+    ("大 (128MB - 1GB)", 128 << 20, 1 << 30),
+    ("巨大 (1GB - 4GB)", 1 << 30, 4 << 30),
+    ("超大 (> 4GB)", 4 << 30, u64::MAX),
+];
+
+#[derive(Default)]
+pub struct RawFacets {
+    ext: HashMap<String, u64>,
+    size: [u64; 7],
+    // 按本地日期（自 1970-01-01 起的天数）计数，汇总时再折算为年与月
+    days: HashMap<i64, u64>,
+    dirs: HashMap<String, u64>,
+    total: u64,
+}
+
+impl RawFacets {
+    fn merge(&mut self, other: RawFacets) {
+        for (k, v) in other.ext { *self.ext.entry(k).or_default() += v; }
+        for (a, b) in self.size.iter_mut().zip(other.size) { *a += b; }
+        for (k, v) in other.days { *self.days.entry(k).or_default() += v; }
+        for (k, v) in other.dirs { *self.dirs.entry(k).or_default() += v; }
+        self.total += other.total;
+    }
+}
+
+pub struct FacetCollector {
+    // 本地时区相对 UTC 的偏移（秒），按本地日期归类修改时间
+    utc_offset: i64,
+}
+
+impl FacetCollector {
+    pub fn new() -> Self {
+        FacetCollector { utc_offset: Local::now().offset().local_minus_utc() as i64 }
+    }
+}
+
+impl Default for FacetCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct FacetSegmentCollector {
+    ext: Option<StrColumn>,
+    size: Option<Column<u64>>,
+    modified: Option<Column<i64>>,
+    path_tree: Option<StrColumn>,
+    utc_offset: i64,
+    ext_counts: HashMap<u64, u64>,
+    dir_counts: HashMap<u64, u64>,
+    raw: RawFacets,
+}
+
+impl Collector for FacetCollector {
+    type Fruit = RawFacets;
+    type Child = FacetSegmentCollector;
+
+    fn for_segment(&self, _segment_ord: SegmentOrdinal, reader: &SegmentReader) -> tantivy::Result<FacetSegmentCollector> {
+        let ff = reader.fast_fields();
+        Ok(FacetSegmentCollector {
+            ext: ff.str("ext")?,
+            size: ff.u64("size").ok(),
+            modified: ff.i64("modified_ts").ok(),
+            path_tree: ff.str("path_tree")?,
+            utc_offset: self.utc_offset,
+            ext_counts: HashMap::new(),
+            dir_counts: HashMap::new(),
+            raw: RawFacets::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, fruits: Vec<RawFacets>) -> tantivy::Result<RawFacets> {
+        let mut all = RawFacets::default();
+        for f in fruits { all.merge(f); }
+        Ok(all)
+    }
+}
+
+impl SegmentCollector for FacetSegmentCollector {
+    type Fruit = RawFacets;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        self.raw.total += 1;
+        if let Some(ord) = self.ext.as_ref().and_then(|c| c.term_ords(doc).next()) {
+            *self.ext_counts.entry(ord).or_default() += 1;
+        }
+        if let Some(size) = self.size.as_ref().and_then(|c| c.first(doc)) {
+            if let Some(i) = SIZE_BUCKETS.iter().position(|(_, lo, hi)| size >= *lo && size < *hi) { self.raw.size[i] += 1; }
+        }
+        if let Some(ts) = self.modified.as_ref().and_then(|c| c.first(doc)) {
+            *self.raw.days.entry((ts + self.utc_offset).div_euclid(86400)).or_default() += 1;
+        }
+        // 路径层级 token 按字典序排列，祖先目录在前、完整路径最后；只统计祖先目录
+        if let Some(col) = &self.path_tree {
+            let ords: Vec<u64> = col.term_ords(doc).collect();
+            for ord in ords.iter().take(ords.len().saturating_sub(1)) {
+                *self.dir_counts.entry(*ord).or_default() += 1;
+            }
+        }
+    }
+
+    fn harvest(mut self) -> RawFacets {
+        let mut buf = String::new();
+        let mut resolve = |col: &Option<StrColumn>, counts: HashMap<u64, u64>, out: &mut HashMap<String, u64>| {
+            let Some(col) = col else { return };
+            for (ord, n) in counts {
+                buf.clear();
+                if col.ord_to_str(ord, &mut buf).unwrap_or(false) { *out.entry(buf.clone()).or_default() += n; }
+            }
+        };
+        resolve(&self.ext, std::mem::take(&mut self.ext_counts), &mut self.raw.ext);
+        resolve(&self.path_tree, std::mem::take(&mut self.dir_counts), &mut self.raw.dirs);
+        self.raw
+    }
+}
+
+fn local_ts(d: NaiveDate) -> Option<i64> {
+    Local.from_local_datetime(&d.and_hms_opt(0, 0, 0)?).earliest().map(|t| t.timestamp())
+}
+
+fn top(mut values: Vec<FacetCount>, n: usize) -> Vec<FacetCount> {
+    values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    values.truncate(n);
+    values
+}
+
+// 汇总为前端展示的分面；base 为已归一化的路径前缀过滤条件
+pub fn finalize(raw: RawFacets, base: Option<String>) -> Facets {
+    let ext = top(raw.ext.into_iter().map(|(value, count)| FacetCount { value, count, from: None, to: None }).collect(), MAX_VALUES);
+
+    let size = SIZE_BUCKETS
+        .iter()
+        .zip(raw.size)
+        .filter(|(_, n)| *n > 0)
+        .map(|((name, lo, hi), count)| FacetCount {
+            value: name.to_string(),
+            count,
+            from: Some(*lo as i64),
+            to: (*hi != u64::MAX).then_some(*hi as i64),
+        })
+        .collect();
+
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    let mut years: HashMap<i32, u64> = HashMap::new();
+    let mut months: HashMap<(i32, u32), u64> = HashMap::new();
+    for (day, n) in raw.days {
+        let d = epoch + Duration::days(day);
+        *years.entry(d.year()).or_default() += n;
+        *months.entry((d.year(), d.month())).or_default() += n;
+    }
+    let mut year: Vec<FacetCount> = years
+        .into_iter()
+        .map(|(y, count)| FacetCount {
+            value: y.to_string(),
+            count,
+            from: NaiveDate::from_ymd_opt(y, 1, 1).and_then(local_ts),
+            to: NaiveDate::from_ymd_opt(y + 1, 1, 1).and_then(local_ts),
+        })
+        .collect();
+    year.sort_by(|a, b| b.value.cmp(&a.value));
+    let mut month: Vec<FacetCount> = months
+        .into_iter()
+        .map(|((y, m), count)| {
+            let next = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+            FacetCount {
+                value: format!("{:04}-{:02}", y, m),
+                count,
+                from: NaiveDate::from_ymd_opt(y, m, 1).and_then(local_ts),
+                to: NaiveDate::from_ymd_opt(next.0, next.1, 1).and_then(local_ts),
+            }
+        })
+        .collect();
+    month.sort_by(|a, b| b.value.cmp(&a.value));
+    month.truncate(MAX_MONTHS);
+
+    // 未指定路径前缀时，向下定位到包含全部命中的最深目录，使目录分面展示有区分度的下一级
+    let dir_base = base.unwrap_or_else(|| {
+        raw.dirs
+            .iter()
+            .filter(|(_, n)| **n == raw.total)
+            .map(|(d, _)| d.clone())
+            .max_by_key(|d| d.len())
+            .unwrap_or_default()
+    });
+    let child_prefix = format!("{}/", dir_base);
+    let dir = top(
+        raw.dirs
+            .into_iter()
+            .filter(|(d, _)| {
+                if dir_base.is_empty() { return d.match_indices('/').filter(|(i, _)| *i > 0).count() == 0; }
+                d.strip_prefix(child_prefix.as_str()).is_some_and(|rest| !rest.is_empty() && !rest.contains('/'))
+            })
+            .map(|(value, count)| FacetCount { value, count, from: None, to: None })
+            .collect(),
+        MAX_VALUES,
+    );
+
+    Facets { ext, size, year, month, dir_base, dir }
+}
+
+// 合并多个索引的分面（多集合检索）：同名取值累加；各自的目录分面基准不同时，基准置空
+pub fn merge(parts: Vec<Facets>) -> Facets {
+    fn sum(lists: Vec<Vec<FacetCount>>) -> Vec<FacetCount> {
+        let mut out: Vec<FacetCount> = Vec::new();
+        for v in lists.into_iter().flatten() {
+            match out.iter_mut().find(|o| o.value == v.value) {
+                Some(o) => o.count += v.count,
+                None => out.push(v),
+            }
+        }
+        out
+    }
+    let same_base = parts.windows(2).all(|w| w[0].dir_base == w[1].dir_base);
+    let dir_base = if same_base { parts.first().map(|p| p.dir_base.clone()).unwrap_or_default() } else { String::new() };
+    let (mut ext, mut size, mut year, mut month, mut dir) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for p in parts {
+        ext.push(p.ext);
+        size.push(p.size);
+        year.push(p.year);
+        month.push(p.month);
+        dir.push(p.dir);
+    }
+    let mut size = sum(size);
+    size.sort_by_key(|f| f.from);
+    let mut year = sum(year);
+    year.sort_by(|a, b| b.value.cmp(&a.value));
+    let mut month = sum(month);
+    month.sort_by(|a, b| b.value.cmp(&a.value));
+    Facets { ext: top(sum(ext), MAX_VALUES), size, year, month, dir_base, dir: top(sum(dir), MAX_VALUES) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{self, IndexFields};
+    use crate::scanner::FileMeta;
+    use tantivy::{query::AllQuery, Index, TantivyDocument};
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        Local.from_local_datetime(&NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()).earliest().unwrap().timestamp()
+    }
+
+    fn facets_of(entries: &[(&str, u64, i64)], base: Option<&str>) -> Facets {
+        let index = Index::create_in_ram(indexer::build_schema());
+        indexer::register_tokenizers(&index);
+        let f = IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(15_000_000).unwrap();
+        for (path, size, modified_ts) in entries {
+            let name = path.rsplit('/').next().unwrap().to_string();
+            let ext = name.rsplit_once('.').map(|(_, e)| e.to_string()).unwrap_or_default();
+            let fm = FileMeta { path: path.to_string(), file_name: name, ext, size: *size, modified_ts: *modified_ts, kind: "file".into(), child_count: None, raw_path: None };
+            writer.add_document(indexer::make_doc(&f, &fm, 0)).unwrap();
+        }
+        writer.commit().unwrap();
+        let raw = index.reader().unwrap().searcher().search(&AllQuery, &FacetCollector::new()).unwrap();
+        finalize(raw, base.map(str::to_string))
+    }
+
+    fn counts(values: &[FacetCount]) -> Vec<(&str, u64)> {
+        values.iter().map(|v| (v.value.as_str(), v.count)).collect()
+    }
+
+    #[test]
+    fn buckets_sizes_at_boundaries() {
+        let ts = local(2024, 5, 1, 12, 30);
+        let sizes = [0, 1, (16 << 10) - 1, 16 << 10, 1 << 20, (128 << 20) - 1, 5 << 30];
+        let entries: Vec<(String, u64, i64)> = sizes.iter().enumerate().map(|(i, s)| (format!("/d/{}.bin", i), *s, ts)).collect();
+        let entries: Vec<(&str, u64, i64)> = entries.iter().map(|(p, s, t)| (p.as_str(), *s, *t)).collect();
+        let facets = facets_of(&entries, None);
+        assert_eq!(counts(&facets.size), [("空 (0)", 1), ("极小 (< 16KB)", 2), ("小 (16KB - 1MB)", 1), ("中 (1MB - 128MB)", 2), ("超大 (> 4GB)", 1)]);
+        let small = &facets.size[2];
+        assert_eq!((small.from, small.to), (Some(16 << 10), Some(1 << 20)));
+        assert_eq!(facets.size.last().unwrap().to, None);
+        assert_eq!(counts(&facets.ext), [("bin", 7)]);
+    }
+
+    #[test]
+    fn buckets_local_years_and_months() {
+        let facets = facets_of(&[
+            ("/d/a.txt", 1, local(2023, 12, 31, 23, 30)),
+            ("/d/b.txt", 1, local(2024, 1, 1, 0, 30)),
+            ("/d/c.txt", 1, local(2024, 1, 20, 12, 30)),
+            ("/d/d.txt", 1, local(2024, 3, 1, 0, 30)),
+        ], None);
+        assert_eq!(counts(&facets.year), [("2024", 3), ("2023", 1)]);
+        assert_eq!(counts(&facets.month), [("2024-03", 1), ("2024-01", 2), ("2023-12", 1)]);
+        let jan = &facets.month[1];
+        assert_eq!((jan.from, jan.to), (Some(local(2024, 1, 1, 0, 0)), Some(local(2024, 2, 1, 0, 0))));
+        let y2023 = &facets.year[1];
+        assert_eq!(y2023.to, Some(local(2024, 1, 1, 0, 0)));
+    }
+
+    #[test]
+    fn counts_directories_below_the_common_base() {
+        let ts = local(2024, 5, 1, 12, 30);
+        let entries = [("/data/a/x.txt", 1, ts), ("/data/a/y.pdf", 1, ts), ("/data/b/z.txt", 1, ts), ("/data/b/c/w.txt", 1, ts)];
+        let facets = facets_of(&entries, None);
+        assert_eq!(facets.dir_base, "/data");
+        assert_eq!(counts(&facets.dir), [("/data/a", 2), ("/data/b", 2)]);
+        // 指定路径前缀时以其为基准，只统计下一级目录（不含直接位于其下的文件）
+        let under_b = facets_of(&entries, Some("/data/b"));
+        assert_eq!(under_b.dir_base, "/data/b");
+        assert_eq!(counts(&under_b.dir), [("/data/b/c", 1)]);
+        // 命中分布在不同顶层目录时以根为基准
+        let spread = facets_of(&[("/data/a/x.txt", 1, ts), ("/srv/y.txt", 1, ts)], None);
+        assert_eq!(spread.dir_base, "");
+        assert_eq!(counts(&spread.dir), [("/data", 1), ("/srv", 1)]);
+
+        let merged = merge(vec![facets, under_b]);
+        assert_eq!(merged.dir_base, "");
+        assert_eq!(counts(&merged.dir), [("/data/a", 2), ("/data/b", 2), ("/data/b/c", 1)]);
+        assert_eq!(counts(&merged.ext), [("txt", 6), ("pdf", 2)]);
+    }
+}
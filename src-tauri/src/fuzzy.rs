@@ -0,0 +1,157 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use tantivy::query::{BooleanQuery, FuzzyTermQuery, Occur, Query, TermSetQuery};
+use tantivy::schema::Field;
+use tantivy::{Index, Searcher, Term};
+use log::debug;
+
+// 容错检索参数：distance 为允许的编辑距离（含相邻字符换位），prefix_len 为必须完全一致的前缀字符数
+#[derive(Debug, Clone, Copy)]
+pub struct FuzzyOptions {
+    pub distance: u8,
+    pub prefix_len: usize,
+}
+
+impl FuzzyOptions {
+    // 编辑距离上限为 2，更大的距离几乎匹配任意短词且自动机构建代价高
+    pub fn new(distance: Option<u8>, prefix_len: Option<usize>) -> Self {
+        FuzzyOptions { distance: distance.unwrap_or(1).clamp(1, 2), prefix_len: prefix_len.unwrap_or(0) }
+    }
+}
+
+// 短于该长度的词不做容错（如 "ab" 在距离 1 下会命中大量无关词）
+const MIN_FUZZY_CHARS: usize = 3;
+// 前缀扫描时每个词最多展开的候选数量
+const MAX_EXPANSIONS: usize = 64;
+
+// 为 name 字段构造容错查询：按字段分词器切分查询文本，每个词生成一个模糊子查询（Should 组合）；
+// 无可用词时返回 None
+pub fn name_query(index: &Index, searcher: &Searcher, field: Field, text: &str, opts: FuzzyOptions) -> Result<Option<Box<dyn Query>>> {
+    let mut analyzer = index.tokenizer_for_field(field)?;
+    let mut words = BTreeSet::new();
+    let mut stream = analyzer.token_stream(text);
+    while stream.advance() {
+        let w = stream.token().text.clone();
+        if w.chars().count() >= MIN_FUZZY_CHARS { words.insert(w); }
+    }
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for w in words {
+        let q: Box<dyn Query> = if opts.prefix_len == 0 {
+            Box::new(FuzzyTermQuery::new(Term::from_field_text(field, &w), opts.distance, true))
+        } else {
+            let terms = expand_with_prefix(searcher, field, &w, opts)?;
+            debug!("fuzzy expand '{}': {} term(s)", w, terms.len());
+            if terms.is_empty() { continue; }
+            Box::new(TermSetQuery::new(terms.iter().map(|t| Term::from_field_text(field, t))))
+        };
+        clauses.push((Occur::Should, q));
+    }
+    if clauses.is_empty() { return Ok(None); }
+    Ok(Some(Box::new(BooleanQuery::new(clauses))))
+}
+
+// 要求前缀一致时，在各段词典中按前缀范围扫描，逐个计算编辑距离
+fn expand_with_prefix(searcher: &Searcher, field: Field, word: &str, opts: FuzzyOptions) -> Result<BTreeSet<String>> {
+    let prefix: String = word.chars().take(opts.prefix_len).collect();
+    let mut out = BTreeSet::new();
+    for seg in searcher.segment_readers() {
+        let inverted = seg.inverted_index(field)?;
+        let mut terms = inverted.terms().range().ge(prefix.as_bytes()).into_stream()?;
+        while terms.advance() {
+            let key = match std::str::from_utf8(terms.key()) { Ok(k) => k, Err(_) => continue };
+            if !key.starts_with(prefix.as_str()) { break; }
+            if distance(word, key) <= opts.distance as usize {
+                out.insert(key.to_string());
+                if out.len() >= MAX_EXPANSIONS { return Ok(out); }
+            }
+        }
+    }
+    Ok(out)
+}
+
+// 编辑距离（插入、删除、替换与相邻换位各计 1，即 OSA 距离），与 FuzzyTermQuery 的换位设置一致
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+    if n.abs_diff(m) > 2 { return n.abs_diff(m); }
+    let mut d = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in d.iter_mut().enumerate() { row[0] = i; }
+    for (j, cell) in d[0].iter_mut().enumerate() { *cell = j; }
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[n][m]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{self, IndexFields};
+    use crate::scanner::FileMeta;
+    use tantivy::collector::Count;
+
+    #[test]
+    fn distance_counts_transpositions_as_one_edit() {
+        assert_eq!(distance("report", "report"), 0);
+        assert_eq!(distance("reprot", "report"), 1);
+        assert_eq!(distance("rport", "report"), 1);
+        assert_eq!(distance("repoort", "report"), 1);
+        assert_eq!(distance("rebort", "report"), 1);
+        assert_eq!(distance("rpeort", "report"), 1);
+        assert_eq!(distance("erpotr", "report"), 2);
+        assert_eq!(distance("报表", "报告"), 1);
+        // 长度差超过 2 时直接返回长度差
+        assert_eq!(distance("rep", "report"), 3);
+    }
+
+    #[test]
+    fn options_clamp_distance_to_one_or_two() {
+        assert_eq!(FuzzyOptions::new(None, None).distance, 1);
+        assert_eq!(FuzzyOptions::new(Some(0), None).distance, 1);
+        assert_eq!(FuzzyOptions::new(Some(2), Some(1)).distance, 2);
+        assert_eq!(FuzzyOptions::new(Some(9), None).distance, 2);
+        assert_eq!(FuzzyOptions::new(None, None).prefix_len, 0);
+    }
+
+    fn index_with(names: &[&str]) -> (Index, IndexFields) {
+        let index = Index::create_in_ram(indexer::build_schema());
+        indexer::register_tokenizers(&index);
+        let f = IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<tantivy::TantivyDocument> = index.writer(15_000_000).unwrap();
+        for name in names {
+            let fm = FileMeta { path: format!("/docs/{}", name), file_name: name.to_string(), ext: "txt".into(), size: 1, modified_ts: 1, kind: "file".into(), child_count: None, raw_path: None };
+            writer.add_document(indexer::make_doc(&f, &fm, 0)).unwrap();
+        }
+        writer.commit().unwrap();
+        (index, f)
+    }
+
+    fn count(index: &Index, f: &IndexFields, text: &str, opts: FuzzyOptions) -> usize {
+        let searcher = index.reader().unwrap().searcher();
+        match name_query(index, &searcher, f.name, text, opts).unwrap() {
+            Some(q) => searcher.search(&q, &Count).unwrap(),
+            None => 0,
+        }
+    }
+
+    #[test]
+    fn name_query_respects_distance_and_prefix() {
+        let (index, f) = index_with(&["report.txt", "export.txt", "rapport.txt"]);
+        // reprot → report（换位，距离 1）
+        assert_eq!(count(&index, &f, "reprot", FuzzyOptions::new(None, None)), 1);
+        // rxpxrt 与 report、export 距离均为 2
+        assert_eq!(count(&index, &f, "rxpxrt", FuzzyOptions::new(Some(1), None)), 0);
+        assert_eq!(count(&index, &f, "rxpxrt", FuzzyOptions::new(Some(2), None)), 2);
+        // 要求首字符一致时排除 export
+        assert_eq!(count(&index, &f, "rxpxrt", FuzzyOptions::new(Some(2), Some(1))), 1);
+        // 过短的词不做容错
+        assert!(name_query(&index, &index.reader().unwrap().searcher(), f.name, "rp", FuzzyOptions::new(None, None)).unwrap().is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use walkdir::DirEntry;
+use log::{debug, warn};
+
+// 应用自定义忽略文件名：语法与 .gitignore 相同，优先级最高
+pub const CUSTOM_IGNORE_FILE: &str = ".searcheveryignore";
+
+// 各规则来源排除的路径数量（被排除的目录计 1 次，其子项不再计数）
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IgnoreStats {
+    pub searcheveryignore: usize,
+    pub ignore: usize,
+    pub gitignore: usize,
+    pub git_exclude: usize,
+    pub git_global: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleSource {
+    Custom,
+    Ignore,
+    GitIgnore,
+    GitExclude,
+}
+
+// 单个目录下加载的规则
+struct DirRules {
+    depth: usize,
+    // 该目录为 git 仓库根目录（含 .git 目录或 worktree/submodule 的 .git 文件）
+    repo_root: bool,
+    custom: Gitignore,
+    ignore: Gitignore,
+    gitignore: Gitignore,
+    git_exclude: Gitignore,
+}
+
+impl DirRules {
+    fn load(dir: &Path, depth: usize) -> Self {
+        let repo_root = dir.join(".git").exists();
+        let git_exclude = if dir.join(".git").is_dir() {
+            load_file(dir, &dir.join(".git").join("info").join("exclude"))
+        } else {
+            Gitignore::empty()
+        };
+        DirRules {
+            depth,
+            repo_root,
+            custom: load_file(dir, &dir.join(CUSTOM_IGNORE_FILE)),
+            ignore: load_file(dir, &dir.join(".ignore")),
+            gitignore: load_file(dir, &dir.join(".gitignore")),
+            git_exclude,
+        }
+    }
+
+    fn get(&self, source: RuleSource) -> &Gitignore {
+        match source {
+            RuleSource::Custom => &self.custom,
+            RuleSource::Ignore => &self.ignore,
+            RuleSource::GitIgnore => &self.gitignore,
+            RuleSource::GitExclude => &self.git_exclude,
+        }
+    }
+}
+
+fn load_file(root: &Path, file: &Path) -> Gitignore {
+    if !file.is_file() { return Gitignore::empty(); }
+    let mut builder = GitignoreBuilder::new(root);
+    if let Some(e) = builder.add(file) { warn!("ignore file partially invalid: {:?}: {}", file, e); }
+    match builder.build() {
+        Ok(gi) => gi,
+        Err(e) => { warn!("ignore file build failed: {:?}: {}", file, e); Gitignore::empty() }
+    }
+}
+
+// 遍历过程中的忽略过滤器：按目录深度维护规则栈，需按 WalkDir 的先序顺序逐个调用 is_ignored
+pub struct IgnoreFilter {
+    global: Gitignore,
+    stack: Vec<DirRules>,
+    pub stats: IgnoreStats,
+}
+
+impl Default for IgnoreFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IgnoreFilter {
+    pub fn new() -> Self {
+        let (global, err) = Gitignore::global();
+        if let Some(e) = err { warn!("global git excludes partially invalid: {}", e); }
+        IgnoreFilter { global, stack: Vec::new(), stats: IgnoreStats::default() }
+    }
+
+    // 扫描根目录位于 git 仓库内（而非仓库根）时，加载从仓库根到根目录上一级的规则文件，
+    // 与 git 一致：扫描仓库的子目录时仓库根的 .gitignore 仍然生效。不在仓库内时不向上查找
+    fn load_ancestors(&mut self, root: &Path) {
+        // 规则按路径前缀匹配，需与遍历产出的路径形式一致：不做规范化，相对路径不向上查找
+        if !root.is_absolute() { return; }
+        let ancestors: Vec<&Path> = root.ancestors().skip(1).collect();
+        let Some(repo) = ancestors.iter().position(|d| d.join(".git").exists()) else { return };
+        for dir in ancestors[..=repo].iter().rev() {
+            debug!("loading ancestor ignore rules: {}", dir.display());
+            self.stack.push(DirRules::load(dir, 0));
+        }
+    }
+
+    // 判断条目是否被忽略；未被忽略的目录会加载其下的规则文件供子项使用
+    // 优先级与 ripgrep 一致：.searcheveryignore > .ignore > .gitignore > .git/info/exclude > 全局排除，
+    // 同一来源内越深的目录优先
+    pub fn is_ignored(&mut self, entry: &DirEntry) -> bool {
+        let depth = entry.depth();
+        while self.stack.last().map(|r| r.depth >= depth).unwrap_or(false) {
+            self.stack.pop();
+        }
+        let is_dir = entry.file_type().is_dir();
+        // 新的扫描根目录：先加载其所在仓库中上级目录的规则
+        if depth == 0 { self.load_ancestors(entry.path()); }
+        // 扫描根目录本身不做判断
+        if depth > 0 {
+            let path = entry.path();
+            for source in [RuleSource::Custom, RuleSource::Ignore, RuleSource::GitIgnore, RuleSource::GitExclude] {
+                for rules in self.stack.iter().rev() {
+                    match rules.get(source).matched(path, is_dir) {
+                        Match::Ignore(_) => {
+                            debug!("ignored by {:?}: {}", source, path.display());
+                            self.count(source);
+                            return true;
+                        }
+                        Match::Whitelist(_) => return self.descend(entry, is_dir),
+                        Match::None => {}
+                    }
+                }
+            }
+            // 全局排除规则（core.excludesFile）只对 git 仓库内的条目生效
+            let in_repo = self.stack.iter().any(|r| r.repo_root);
+            if in_repo && self.global.matched(path, is_dir).is_ignore() {
+                debug!("ignored by global git excludes: {}", path.display());
+                self.stats.git_global += 1;
+                return true;
+            }
+        }
+        self.descend(entry, is_dir)
+    }
+
+    fn descend(&mut self, entry: &DirEntry, is_dir: bool) -> bool {
+        if is_dir {
+            self.stack.push(DirRules::load(entry.path(), entry.depth()));
+        }
+        false
+    }
+
+    fn count(&mut self, source: RuleSource) {
+        match source {
+            RuleSource::Custom => self.stats.searcheveryignore += 1,
+            RuleSource::Ignore => self.stats.ignore += 1,
+            RuleSource::GitIgnore => self.stats.gitignore += 1,
+            RuleSource::GitExclude => self.stats.git_exclude += 1,
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use tantivy::{schema::*, Index, doc};
-use std::{fs, io::Read};
-use log::info;
+use tantivy::{schema::*, DocAddress, Index, IndexReader, ReloadPolicy, Searcher, SearcherGeneration, Warmer, doc, Term, TantivyDocument};
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::query::{Query, TermQuery};
+use tantivy::tokenizer::{LowerCaser, RawTokenizer, RemoveLongFilter, TextAnalyzer};
+use std::{collections::{BTreeSet, HashMap}, fs, io::{ErrorKind, Read}, path::Path, sync::Arc, time::Instant};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use log::{debug, info, warn};
 
 use crate::scanner::FileMeta;
+use crate::tokenizer::{JiebaTokenizer, PathTokenizer, JIEBA_TOKENIZER, PATH_TOKENIZER};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexOptions {
@@ -12,77 +18,492 @@ pub struct IndexOptions {
     pub enable_content_parse: bool,
 }
 
-pub async fn build(files: Vec<FileMeta>, opts: IndexOptions) -> Result<()> {
-    info!("indexer::build start: files={}, index_dir={}, content_parse={}", files.len(), opts.index_dir, opts.enable_content_parse);
-    let mut schema_builder = Schema::builder();
+// 排序键分词器：整串不切分并转小写（fast 字段专用，需在打开索引时注册）
+pub const SORT_KEY_TOKENIZER: &str = "sort_key";
+
+pub(crate) fn register_tokenizers(index: &Index) {
+    index.tokenizers().register(
+        JIEBA_TOKENIZER,
+        TextAnalyzer::builder(JiebaTokenizer).filter(RemoveLongFilter::limit(40)).filter(LowerCaser).build(),
+    );
+    index.tokenizers().register(PATH_TOKENIZER, TextAnalyzer::from(PathTokenizer));
+    index.fast_field_tokenizer().register(PATH_TOKENIZER, TextAnalyzer::from(PathTokenizer));
+    index.fast_field_tokenizer().register(
+        SORT_KEY_TOKENIZER,
+        TextAnalyzer::builder(RawTokenizer::default()).filter(LowerCaser).build(),
+    );
+}
 
-    // 字段选项在 tantivy 0.22 中需使用 Options 显式设置
-    // 确保 path 可索引，以支持 delete_term/upsert
-    let f_path = schema_builder.add_text_field(
-        "path",
+// 统一的索引 schema：indexer、build_inverted_index_progress 与 pipeline 共用
+// 字段选项在 tantivy 0.22 中需使用 Options 显式设置
+pub fn build_schema() -> Schema {
+    let mut schema_builder = Schema::builder();
+    // path 不分词（STRING），整条路径作为一个 term，以支持 delete_term/upsert；FAST 用于按路径排序
+    schema_builder.add_text_field("path", STRING | STORED | FAST);
+    // 路径层级（见 tokenizer::PathTokenizer）：索引用于按目录前缀过滤，fast 列用于按目录统计分面
+    schema_builder.add_text_field(
+        "path_tree",
         TextOptions::default()
-            .set_stored()
-            .set_indexing_options(TextFieldIndexing::default()),
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer(PATH_TOKENIZER).set_index_option(IndexRecordOption::Basic))
+            .set_fast(Some(PATH_TOKENIZER)),
     );
-    let f_name = schema_builder.add_text_field(
+    // name 与 content 使用 jieba 分词并记录词频与位置（短语与邻近查询需要）；
+    // name 的 fast 列使用小写整串，按名称排序时不区分大小写
+    let text_indexing = TextFieldIndexing::default()
+        .set_tokenizer(JIEBA_TOKENIZER)
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    schema_builder.add_text_field(
         "name",
         TextOptions::default()
             .set_stored()
-            .set_indexing_options(TextFieldIndexing::default()),
-    );
-    let f_ext = schema_builder.add_text_field(
-        "ext",
-        TextOptions::default().set_stored(),
+            .set_indexing_options(text_indexing.clone())
+            .set_fast(Some(SORT_KEY_TOKENIZER)),
     );
-    let f_content = schema_builder.add_text_field(
+    // ext 不分词（STRING），整体作为一个 term，用于扩展名过滤；FAST 用于分面统计
+    schema_builder.add_text_field("ext", STRING | STORED | FAST);
+    schema_builder.add_text_field(
         "content",
-        TextOptions::default().set_indexing_options(TextFieldIndexing::default()),
+        TextOptions::default().set_indexing_options(text_indexing),
     );
+    // 中文名称的拼音全拼与首字母（见 pinyin_names），空格分隔的 token，仅索引
+    schema_builder.add_text_field("name_pinyin", TEXT);
+    schema_builder.add_text_field("name_initials", TEXT);
+    // 正文开头部分（上限 CONTENT_HEAD_MAX_BYTES）：仅存储（文档存储自带压缩），用于生成命中片段
+    schema_builder.add_text_field("content_head", TextOptions::default().set_stored());
     // 摘要片段：存储简短文本，便于结果页展示
-    let f_summary = schema_builder.add_text_field(
-        "summary",
-        TextOptions::default().set_stored(),
+    schema_builder.add_text_field("summary", TextOptions::default().set_stored());
+    // size 与 modified_ts 需 indexed + fast，用于范围过滤
+    schema_builder.add_u64_field("size", NumericOptions::default().set_stored().set_indexed().set_fast());
+    schema_builder.add_i64_field("modified_ts", NumericOptions::default().set_stored().set_indexed().set_fast());
+    // 条目首次写入索引的时间（Unix 秒；重写同一条目时沿用，见 first_indexed_ts）：
+    // 关注的已保存搜索据此只检查新出现的条目（见 search_alerts）
+    schema_builder.add_i64_field("indexed_ts", NumericOptions::default().set_indexed().set_fast());
+    // 条目类型："file" | "dir" | "symlink"
+    schema_builder.add_text_field("kind", STRING | STORED);
+    // 目录的直接子项数量（仅 dir）
+    schema_builder.add_u64_field("child_count", NumericOptions::default().set_stored());
+    // 非 UTF-8 路径的原始字节：存储并索引，作为此类文档 upsert/删除的唯一键
+    schema_builder.add_bytes_field("raw_path", BytesOptions::default().set_stored().set_indexed());
+    schema_builder.build()
+}
+
+// 字段句柄
+#[derive(Debug, Clone, Copy)]
+pub struct IndexFields {
+    pub path: Field,
+    pub path_tree: Field,
+    pub name: Field,
+    pub name_pinyin: Field,
+    pub name_initials: Field,
+    pub ext: Field,
+    pub content: Field,
+    pub content_head: Field,
+    pub summary: Field,
+    pub size: Field,
+    pub modified: Field,
+    pub indexed: Field,
+    pub kind: Field,
+    pub child_count: Field,
+    pub raw_path: Field,
+}
+
+impl IndexFields {
+    pub fn from_schema(schema: &Schema) -> Result<Self> {
+        let get = |name: &str| schema.get_field(name).map_err(|_| anyhow::anyhow!("schema missing field '{}'", name));
+        Ok(IndexFields {
+            path: get("path")?,
+            path_tree: get("path_tree")?,
+            name: get("name")?,
+            name_pinyin: get("name_pinyin")?,
+            name_initials: get("name_initials")?,
+            ext: get("ext")?,
+            content: get("content")?,
+            content_head: get("content_head")?,
+            summary: get("summary")?,
+            size: get("size")?,
+            modified: get("modified_ts")?,
+            indexed: get("indexed_ts")?,
+            kind: get("kind")?,
+            child_count: get("child_count")?,
+            raw_path: get("raw_path")?,
+        })
+    }
+
+    // 删除旧文档时使用的唯一键：非 UTF-8 路径按原始字节，其余按 path
+    pub fn key_term(&self, path: &str, raw_path: Option<&str>) -> Term {
+        match raw_path.and_then(|h| hex::decode(h).ok()) {
+            Some(bytes) => Term::from_field_bytes(self.raw_path, &bytes),
+            None => Term::from_field_text(self.path, path),
+        }
+    }
+}
+
+// 按新 schema 重建时使用的同级目录；重建完成前检索仍使用原目录中的旧索引
+pub fn rebuild_dir(index_dir: &str) -> String {
+    format!("{}.rebuild", index_dir.trim_end_matches(['/', '\\']))
+}
+
+// 是否有尚未替换到位的重建索引
+pub fn is_rebuilding(index_dir: &str) -> bool {
+    Path::new(&rebuild_dir(index_dir)).join("meta.json").exists()
+}
+
+// 打开供写入的索引；若不存在则创建。若磁盘上的 schema 与当前版本不一致（旧版本构建），
+// 改为在同级目录中构建新索引（已有同版本的重建索引则继续使用），由完整扫描结束后的 promote_rebuild 替换；
+// 期间旧索引保持可检索
+pub fn open_or_create(index_dir: &str) -> Result<Index> {
+    let schema = build_schema();
+    fs::create_dir_all(index_dir)?;
+    let index = match Index::open_in_dir(index_dir) {
+        Ok(idx) if idx.schema() == schema => idx,
+        Ok(_) => {
+            let dir = rebuild_dir(index_dir);
+            fs::create_dir_all(&dir)?;
+            match Index::open_in_dir(&dir) {
+                Ok(idx) if idx.schema() == schema => idx,
+                _ => {
+                    warn!("index schema at {} is outdated; rebuilding into {}", index_dir, dir);
+                    remove_index_files(&dir)?;
+                    let _ = crate::pipeline_state::mark_stale(index_dir);
+                    Index::create_in_dir(&dir, schema)?
+                }
+            }
+        }
+        Err(_) => Index::create_in_dir(index_dir, schema)?,
+    };
+    register_tokenizers(&index);
+    Ok(index)
+}
+
+// 打开供检索的索引：保留磁盘上已有的索引（即使 schema 已过时），不存在时才创建
+fn open_for_read(index_dir: &str) -> Result<Index> {
+    let index = match Index::open_in_dir(index_dir) {
+        Ok(idx) => {
+            // 旧版本索引：标记为未完成，使自动扫描尽快在同级目录中重建
+            if idx.schema() != build_schema() && !is_rebuilding(index_dir) { let _ = crate::pipeline_state::mark_stale(index_dir); }
+            idx
+        }
+        Err(_) if is_rebuilding(index_dir) => anyhow::bail!("索引正在按新版本重建，完成后即可检索"),
+        Err(_) => {
+            fs::create_dir_all(index_dir)?;
+            Index::create_in_dir(index_dir, build_schema())?
+        }
+    };
+    register_tokenizers(&index);
+    Ok(index)
+}
+
+// 用重建完成的索引替换旧索引：须在重建目录的 writer 释放后调用。
+// 先移入新索引的段文件（文件名唯一，不与旧文件冲突），再以替换 meta.json 切换到新索引；
+// 切换前任一步失败则移回已移动的文件，旧索引保持不变，下次完整扫描结束后重试。
+// 替换期间持有 SWAP 写锁，检索方不会打开到一半的目录
+pub fn promote_rebuild(index_dir: &str) -> Result<()> {
+    let dir = rebuild_dir(index_dir);
+    let (from, to) = (Path::new(&dir), Path::new(index_dir));
+    let _swap = SWAP.write();
+    invalidate_cached(index_dir);
+    let old_files = managed_files(to);
+    let new_files = managed_files(from);
+    let mut moved = Vec::new();
+    let mut result = Ok(());
+    for entry in fs::read_dir(from)? {
+        let name = entry?.file_name();
+        let n = name.to_string_lossy();
+        if n == "meta.json" || n == ".managed.json" || n.starts_with(".tantivy-") { continue; }
+        if let Err(e) = fs::rename(from.join(&name), to.join(&name)) { result = Err(e); break; }
+        moved.push(name);
+    }
+    if let Err(e) = result.and_then(|_| fs::rename(from.join("meta.json"), to.join("meta.json"))) {
+        for name in moved { let _ = fs::rename(to.join(&name), from.join(&name)); }
+        anyhow::bail!("替换重建索引失败，继续使用旧索引：{}", e);
+    }
+    // 旧索引的文件删除失败（如 Windows 下仍被映射）时登记为受管文件，由 tantivy 之后的垃圾回收清理
+    let mut managed = new_files.clone();
+    for f in old_files.difference(&new_files) {
+        match fs::remove_file(to.join(f)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => { warn!("remove old index file {} failed: {}", f, e); managed.insert(f.clone()); }
+            _ => {}
+        }
+    }
+    fs::write(to.join(".managed.json"), serde_json::to_string(&managed)?)?;
+    if let Err(e) = fs::remove_dir_all(from) { warn!("remove rebuild dir {} failed: {}", dir, e); }
+    info!("rebuilt index promoted: {} -> {}", dir, index_dir);
+    Ok(())
+}
+
+// 旧版本索引（缺少当前 schema 的字段）：按新版本重建完成前只读打开，供降级检索使用
+pub struct LegacyIndex {
+    pub index: Index,
+    pub reader: IndexReader,
+}
+
+static LEGACY_INDEXES: Lazy<Mutex<HashMap<String, Arc<LegacyIndex>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// 打开（并缓存）旧版本索引；磁盘上的索引不缺字段或不存在时返回 None
+pub fn open_legacy(index_dir: &str) -> Result<Option<Arc<LegacyIndex>>> {
+    if let Some(old) = LEGACY_INDEXES.lock().get(index_dir) { return Ok(Some(old.clone())); }
+    let _swap = SWAP.read();
+    let Ok(index) = Index::open_in_dir(index_dir) else { return Ok(None) };
+    if IndexFields::from_schema(&index.schema()).is_ok() { return Ok(None); }
+    register_tokenizers(&index);
+    let reader = index.reader_builder().reload_policy(ReloadPolicy::OnCommitWithDelay).try_into()?;
+    info!("legacy index opened read-only: dir={}", index_dir);
+    let old = Arc::new(LegacyIndex { index, reader });
+    Ok(Some(LEGACY_INDEXES.lock().entry(index_dir.to_string()).or_insert(old).clone()))
+}
+
+// 已打开的索引：检索共用同一个 Index 与 IndexReader，避免每次查询重新打开。
+// reader 在写入方提交后自动重新加载（OnCommitWithDelay，约 500ms 内可见），新的 searcher 由 FieldWarmer 预热
+pub struct OpenIndex {
+    pub index: Index,
+    pub reader: IndexReader,
+    pub fields: IndexFields,
+    // reader 仅持有弱引用，预热器需随缓存项一同存活
+    _warmer: Arc<FieldWarmer>,
+}
+
+static OPEN_INDEXES: Lazy<Mutex<HashMap<String, Arc<OpenIndex>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 打开索引（读）与替换重建索引（写）互斥
+static SWAP: Lazy<RwLock<()>> = Lazy::new(|| RwLock::new(()));
+
+// 获取（必要时打开并缓存）index_dir 对应的索引
+pub fn open_cached(index_dir: &str) -> Result<Arc<OpenIndex>> {
+    if let Some(open) = OPEN_INDEXES.lock().get(index_dir) { return Ok(open.clone()); }
+    let _swap = SWAP.read();
+    let started = Instant::now();
+    let index = open_for_read(index_dir)?;
+    // 旧版本索引缺少字段时无法检索，需等待重建完成
+    let fields = IndexFields::from_schema(&index.schema())
+        .map_err(|e| if is_rebuilding(index_dir) { anyhow::anyhow!("索引正在按新版本重建，完成后即可检索") } else { e })?;
+    let warmer = Arc::new(FieldWarmer { fields });
+    let weak: std::sync::Weak<dyn Warmer> = Arc::downgrade(&(warmer.clone() as Arc<dyn Warmer>));
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .warmers(vec![weak])
+        .try_into()?;
+    let open = Arc::new(OpenIndex { index, reader, fields, _warmer: warmer });
+    info!("index opened and cached: dir={}, elapsed_ms={}", index_dir, started.elapsed().as_millis());
+    // 并发打开时以先写入者为准，保证同一目录只有一个 reader
+    Ok(OPEN_INDEXES.lock().entry(index_dir.to_string()).or_insert(open).clone())
+}
+
+// 释放缓存的索引（如配置中的 index_dir 已变更）；正在使用的查询持有 Arc，结束后自然释放
+pub fn invalidate_cached(index_dir: &str) {
+    let legacy = LEGACY_INDEXES.lock().remove(index_dir).is_some();
+    if OPEN_INDEXES.lock().remove(index_dir).is_some() || legacy {
+        info!("index cache invalidated: dir={}", index_dir);
+    }
+}
+
+// 预热：加载各段的词典与排序/过滤用的 fast 列，使 reload 后的首次查询不承担冷启动开销
+struct FieldWarmer {
+    fields: IndexFields,
+}
+
+impl Warmer for FieldWarmer {
+    fn warm(&self, searcher: &Searcher) -> tantivy::Result<()> {
+        let started = Instant::now();
+        for seg in searcher.segment_readers() {
+            for field in [self.fields.name, self.fields.content, self.fields.path_tree, self.fields.ext] {
+                seg.inverted_index(field)?;
+            }
+            let fast = seg.fast_fields();
+            let _ = fast.u64("size");
+            let _ = fast.i64("modified_ts");
+        }
+        debug!("searcher warmed: segments={}, elapsed_ms={}", searcher.segment_readers().len(), started.elapsed().as_millis());
+        Ok(())
+    }
+
+    fn garbage_collect(&self, _live_generations: &[&SearcherGeneration]) {}
+}
+
+// .managed.json 中登记的文件（tantivy 管理的文件，不含 .managed.json 本身）
+fn managed_files(dir: &Path) -> BTreeSet<String> {
+    fs::read_to_string(dir.join(".managed.json")).ok().and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+// 仅删除 tantivy 管理的文件（.managed.json 中登记的文件及元数据），不触碰目录内其他文件；
+// 任一文件删除失败时返回错误，不在残留文件上创建新索引
+fn remove_index_files(index_dir: &str) -> Result<()> {
+    let dir = Path::new(index_dir);
+    let mut files: Vec<String> = managed_files(dir).into_iter().collect();
+    files.extend(["meta.json".to_string(), ".managed.json".to_string()]);
+    for f in files {
+        match fs::remove_file(dir.join(&f)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => anyhow::bail!("删除旧索引文件 {} 失败：{}", f, e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// 待删除的条目：(path, raw_path)
+type EntryKey = (String, Option<String>);
+
+// 写入方被占用时推迟的删除（按索引目录），由占用 writer 的构建结束后补上
+static PENDING_DELETES: Lazy<Mutex<HashMap<String, Vec<EntryKey>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// 写入方使用的目录：按新版本重建期间为同级的重建目录
+pub fn write_dir(index_dir: &str) -> String {
+    if is_rebuilding(index_dir) { rebuild_dir(index_dir) } else { index_dir.to_string() }
+}
+
+// 从 dir 中的索引删除条目（索引不存在时忽略）；writer 被占用（构建进行中）时记入待删除，
+// 由构建结束后的 apply_pending_deletes 补上
+pub fn delete_entry(dir: &str, path: &str, raw_path: Option<&str>) -> Result<()> {
+    let _swap = SWAP.read();
+    let Ok(index) = Index::open_in_dir(dir) else {
+        warn!("index not found at {}; skipping index deletion", dir);
+        return Ok(());
+    };
+    let mut writer: tantivy::IndexWriter<TantivyDocument> = match index.writer_with_num_threads(1, 15_000_000) {
+        Ok(w) => w,
+        Err(tantivy::TantivyError::LockFailure(..)) => {
+            info!("index writer busy at {}; deletion of {} deferred", dir, path);
+            PENDING_DELETES.lock().entry(dir.to_string()).or_default().push((path.to_string(), raw_path.map(str::to_string)));
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    // 与写入时使用相同的唯一键；旧版本索引无 raw_path 字段时退回按 path 删除
+    let schema = index.schema();
+    let term = match IndexFields::from_schema(&schema) {
+        Ok(f) => f.key_term(path, raw_path),
+        Err(_) => Term::from_field_text(schema.get_field("path").map_err(|_| anyhow::anyhow!("schema missing field 'path'"))?, path),
+    };
+    writer.delete_term(term);
+    writer.commit()?;
+    Ok(())
+}
+
+// 补上推迟的删除：须在该目录的 writer 释放后调用
+pub fn apply_pending_deletes(dir: &str) -> Result<usize> {
+    let entries = PENDING_DELETES.lock().remove(dir).unwrap_or_default();
+    for (path, raw_path) in &entries { delete_entry(dir, path, raw_path.as_deref())?; }
+    Ok(entries.len())
+}
+
+// 正文存储上限：超出部分仍参与索引，但命中时无法生成片段，退回摘要展示
+pub const CONTENT_HEAD_MAX_BYTES: usize = 64 * 1024;
+
+// 写入解析出的正文：全文索引、截断存储与摘要（前 300 个字符）
+pub fn add_content(f: &IndexFields, doc: &mut TantivyDocument, text: &str) {
+    doc.add_text(f.content, text);
+    let mut end = text.len().min(CONTENT_HEAD_MAX_BYTES);
+    while !text.is_char_boundary(end) { end -= 1; }
+    doc.add_text(f.content_head, &text[..end]);
+    let summary: String = text.chars().take(300).collect();
+    if !summary.is_empty() {
+        doc.add_text(f.summary, &summary);
+    }
+}
+
+// 由扫描结果生成文档（不含正文）；indexed_ts 为条目首次写入索引的时间
+pub fn make_doc(f: &IndexFields, fm: &FileMeta, indexed_ts: i64) -> TantivyDocument {
+    let mut doc = doc!(
+        f.path => fm.path.clone(),
+        f.path_tree => fm.path.clone(),
+        f.name => fm.file_name.clone(),
+        f.ext => fm.ext.clone(),
+        f.size => fm.size,
+        f.modified => fm.modified_ts,
+        f.indexed => indexed_ts,
+        f.kind => fm.kind.clone(),
     );
-    let f_size = schema_builder.add_u64_field("size", NumericOptions::default().set_stored());
-    let f_modified = schema_builder.add_i64_field("modified_ts", NumericOptions::default().set_stored());
-    let schema = schema_builder.build();
+    if let Some(py) = crate::pinyin_names::variants(&fm.file_name) {
+        doc.add_text(f.name_pinyin, &py.full);
+        doc.add_text(f.name_initials, &py.initials);
+    }
+    if let Some(n) = fm.child_count {
+        doc.add_u64(f.child_count, n);
+    }
+    if let Some(bytes) = fm.raw_path.as_deref().and_then(|h| hex::decode(h).ok()) {
+        doc.add_bytes(f.raw_path, bytes);
+    }
+    doc
+}
 
-    std::fs::create_dir_all(&opts.index_dir)?;
-    let index = Index::create_in_dir(&opts.index_dir, schema.clone())?;
+// 索引中已有同一条目（相同唯一键）时其首次写入时间；重新扫描或重写时沿用，
+// 使关注提醒只针对新出现的条目
+pub fn first_indexed_ts(searcher: &Searcher, f: &IndexFields, fm: &FileMeta) -> Option<i64> {
+    let query = TermQuery::new(f.key_term(&fm.path, fm.raw_path.as_deref()), IndexRecordOption::Basic);
+    let (_, addr) = searcher.search(&query, &TopDocs::with_limit(1)).ok()?.into_iter().next()?;
+    indexed_ts_of(searcher, addr)
+}
 
-    let mut writer = index.writer(50_000_000)?; // 50MB
+fn indexed_ts_of(searcher: &Searcher, addr: DocAddress) -> Option<i64> {
+    searcher.segment_reader(addr.segment_ord).fast_fields().i64("indexed_ts").ok()?.first(addr.doc_id)
+}
+
+pub async fn build(files: Vec<FileMeta>, opts: IndexOptions) -> Result<()> {
+    info!("indexer::build start: files={}, index_dir={}, content_parse={}", files.len(), opts.index_dir, opts.enable_content_parse);
+    let index = open_or_create(&opts.index_dir)?;
+    let f = IndexFields::from_schema(&index.schema())?;
+
+    let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(50_000_000)?; // 50MB
+    let prior = index.reader()?.searcher();
 
     let mut processed = 0usize;
     let sample_every: usize = std::env::var("SE_INDEX_LOG_SAMPLE_EVERY").ok().and_then(|v| v.parse().ok()).unwrap_or(500);
     for fm in files {
-        let mut doc = doc!(
-            f_path => fm.path.clone(),
-            f_name => fm.file_name.clone(),
-            f_ext => fm.ext.clone(),
-            f_size => fm.size,
-            f_modified => fm.modified_ts,
-        );
-
-        if opts.enable_content_parse {
+        let mut doc = make_doc(&f, &fm, first_indexed_ts(&prior, &f, &fm).unwrap_or_else(|| chrono::Local::now().timestamp()));
+
+        if opts.enable_content_parse && fm.kind == "file" {
             if let Some(text) = parse_content(&fm).await {
-                doc.add_text(f_content, &text);
-                // 生成简短摘要（前 300 个字符）
-                let summary: String = text.chars().take(300).collect();
-                if !summary.is_empty() {
-                    doc.add_text(f_summary, &summary);
-                }
+                add_content(&f, &mut doc, &text);
             }
         }
+        writer.delete_term(f.key_term(&fm.path, fm.raw_path.as_deref()));
         writer.add_document(doc)?;
         processed += 1;
         if processed % sample_every == 0 { info!("indexer sample[{}]: {}", processed, fm.file_name); }
     }
 
     writer.commit()?;
+    drop(writer);
+    apply_pending_deletes(&write_dir(&opts.index_dir))?;
     info!("indexer::build done: indexed={}", processed);
     Ok(())
 }
 
+// 按当前分词词典重写匹配 query 的文档（用户词典变更后调用）：条目信息由存储字段还原，
+// 原先带正文的重新读取正文
+pub async fn retokenize(index_dir: &str, query: &dyn Query) -> Result<usize> {
+    let open = open_cached(index_dir)?;
+    open.reader.reload()?;
+    let searcher = open.reader.searcher();
+    let f = open.fields;
+    let addrs = searcher.search(query, &DocSetCollector)?;
+    if addrs.is_empty() { return Ok(0); }
+    info!("indexer::retokenize start: docs={}, index_dir={}", addrs.len(), index_dir);
+    let mut writer: tantivy::IndexWriter<TantivyDocument> = open.index.writer(50_000_000)?;
+    for addr in &addrs {
+        let stored: TantivyDocument = searcher.doc(*addr)?;
+        let text = |field: Field| stored.get_first(field).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let fm = FileMeta {
+            path: text(f.path),
+            file_name: text(f.name),
+            ext: text(f.ext),
+            size: stored.get_first(f.size).and_then(|v| v.as_u64()).unwrap_or(0),
+            modified_ts: stored.get_first(f.modified).and_then(|v| v.as_i64()).unwrap_or(0),
+            kind: text(f.kind),
+            child_count: stored.get_first(f.child_count).and_then(|v| v.as_u64()),
+            raw_path: stored.get_first(f.raw_path).and_then(|v| v.as_bytes()).map(hex::encode),
+        };
+        let mut doc = make_doc(&f, &fm, indexed_ts_of(&searcher, *addr).unwrap_or_else(|| chrono::Local::now().timestamp()));
+        if stored.get_first(f.content_head).is_some() {
+            if let Some(text) = parse_content(&fm).await { add_content(&f, &mut doc, &text); }
+        }
+        writer.delete_term(f.key_term(&fm.path, fm.raw_path.as_deref()));
+        writer.add_document(doc)?;
+    }
+    writer.commit()?;
+    info!("indexer::retokenize done: docs={}", addrs.len());
+    Ok(addrs.len())
+}
+
 async fn parse_content(_fm: &FileMeta) -> Option<String> {
     // 先支持纯文本类文件，限制最大读取大小，避免占用过多内存
     let ext = _fm.ext.as_str();
@@ -92,12 +513,12 @@ async fn parse_content(_fm: &FileMeta) -> Option<String> {
     }
     // 最大读取 1MB
     let max_bytes: usize = 1_000_000;
-    let path = std::path::Path::new(&_fm.path);
+    let path = crate::pathenc::resolve(&_fm.path, _fm.raw_path.as_deref());
     if !path.exists() || !path.is_file() { return None; }
-    let mut file = match fs::File::open(path) { Ok(f) => f, Err(_) => return None };
+    let mut file = match fs::File::open(&path) { Ok(f) => f, Err(_) => return None };
     let mut buf = Vec::with_capacity(max_bytes);
     let mut handle = file.by_ref().take(max_bytes as u64);
     if handle.read_to_end(&mut buf).is_err() { return None; }
     let text = String::from_utf8_lossy(&buf).to_string();
     Some(text)
-}
\ No newline at end of file
+}
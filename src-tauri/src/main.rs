@@ -8,6 +8,20 @@ mod config;
 mod pipeline_state;
 mod pipeline;
 mod diagnostics;
+mod ignore_rules;
+mod mounts;
+mod pathenc;
+mod tokenizer;
+mod fuzzy;
+mod name_table;
+mod pinyin_names;
+mod explain;
+mod facets;
+mod open_history;
+mod search_history;
+mod search_alerts;
+mod suggest;
+mod dictionary;
 
 use serde::{Deserialize, Serialize};
 use std::process::Command;
@@ -16,7 +30,6 @@ use log::{info, warn, debug};
 use sysinfo::System;
 use crate::pipeline::{scan_and_index_pipeline_internal, scan_and_index_pipeline, start_auto_scan_now};
 use tauri::Manager; // bring Manager trait for emit_all
-use tantivy::{Index, Term};
 use std::fs;
 
 fn init_logging() {
@@ -71,12 +84,21 @@ fn init_logging() {
     let _ = FILE_GUARDS.set(vec![guard_current, guard_daily]);
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanOptions {
     pub roots: Vec<String>,
     pub exclude_patterns: Vec<String>,
     pub max_file_size_mb: Option<u64>,
     pub follow_symlinks: bool,
+    // 遵循 .gitignore / .ignore / .searcheveryignore 及全局 git 排除规则
+    #[serde(default)]
+    pub respect_ignore_files: bool,
+    // 不跨越文件系统边界（不进入扫描根目录之外的其他挂载点）
+    #[serde(default)]
+    pub one_file_system: bool,
+    // 需跳过的挂载分类（见 mounts::classify），仅 Linux 生效
+    #[serde(default = "mounts::default_skip_kinds")]
+    pub skip_mount_kinds: Vec<String>,
 }
 
 #[tauri::command]
@@ -89,81 +111,202 @@ async fn scan_paths(opts: ScanOptions) -> Result<Vec<scanner::FileMeta>, String>
 // 扫描目录（带进度事件）：实时发送已扫描文件数量
 #[tauri::command]
 async fn scan_paths_progress(opts: ScanOptions, window: tauri::Window) -> Result<Vec<scanner::FileMeta>, String> {
-    use walkdir::WalkDir;
-
-    let mut results: Vec<scanner::FileMeta> = Vec::new();
-    let max_bytes = opts.max_file_size_mb.map(|m| m * 1024 * 1024);
     info!("scan_paths_progress start: roots={:?}", opts.roots);
-    let mut scanned_files: usize = 0;
-    let sample_every: usize = std::env::var("SE_SCAN_LOG_SAMPLE_EVERY").ok().and_then(|v| v.parse().ok()).unwrap_or(200);
-
-    for root in opts.roots {
-        info!("scanning root: {}", root);
-        for entry in WalkDir::new(&root).follow_links(opts.follow_symlinks) {
-            let entry = match entry { Ok(e) => e, Err(_) => continue };
-            if !entry.file_type().is_file() { continue; }
-            let path = entry.path();
-            let path_str = match path.to_str() { Some(s) => s.to_string(), None => continue };
-
-            // exclude patterns（简单包含匹配）
-            if opts.exclude_patterns.iter().any(|p| path_str.contains(p)) {
-                debug!("excluded by pattern: {}", path_str);
-                continue;
-            }
-
-            let md = match path.metadata() { Ok(m) => m, Err(_) => continue };
-            if let Some(mb) = max_bytes { if md.len() > mb { debug!("skip by size (>{} bytes): {}", mb, path_str); continue; } }
-
-            let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string();
-            let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            let modified_ts = md.modified().ok()
-                .and_then(|t| t.elapsed().ok())
-                .map(|e| chrono::Utc::now().timestamp() - e.as_secs() as i64)
-                .unwrap_or(0);
-
-            results.push(scanner::FileMeta {
-                path: path_str.clone(),
-                file_name: file_name.clone(),
-                ext,
-                size: md.len(),
-                modified_ts,
-            });
-
-            scanned_files += 1;
-            if scanned_files % sample_every == 0 {
-                info!("scanning sample[{}]: {}", scanned_files, path_str);
-            }
-
-            let _ = window.emit("scan_progress", json!({
-                "current": results.len(),
-                "path": path_str,
-                "name": file_name,
+    let progress_window = window.clone();
+    let mut iter = scanner::Scanner::new(opts)
+        .on_progress(move |meta, current| {
+            let _ = progress_window.emit("scan_progress", json!({
+                "current": current,
+                "path": meta.path,
+                "name": meta.file_name,
             }));
-        }
-    }
-
-    let _ = window.emit("scan_done", json!({ "total": results.len() }));
+        })
+        .into_iter();
+    let results: Vec<scanner::FileMeta> = iter.by_ref().collect();
+    let _ = window.emit("scan_done", json!({ "total": results.len(), "cancelled": iter.is_cancelled() }));
     info!("scan_paths_progress done: total_files={}", results.len());
     Ok(results)
 }
 
+// 取消正在运行的扫描：targets 的每一项为扫描 id、索引集合名称、索引目录或 "manual"（scan_paths / scan_paths_progress）；
+// 为空时停止全部扫描（包括其他集合的调度扫描）
+#[tauri::command]
+async fn cancel_scan(targets: Option<Vec<String>>) -> Result<usize, String> {
+    let Some(targets) = targets else {
+        let n = scanner::cancel_all();
+        info!("cancel_scan: notified all {} running scan(s)", n);
+        return Ok(n);
+    };
+    let cfg = config::read_config().await.map_err(|e| e.to_string())?;
+    let mut n = 0;
+    for t in &targets {
+        // 集合名称按配置换成其索引目录（管道扫描以索引目录为标签）
+        let label = cfg.collection(t).map(|c| c.index_dir).unwrap_or_else(|| t.clone());
+        n += scanner::cancel(&label);
+    }
+    info!("cancel_scan: targets={:?}, notified {} running scan(s)", targets, n);
+    Ok(n)
+}
+
+// 正在运行的扫描（id 与标签）
+#[tauri::command]
+async fn list_active_scans() -> Result<Vec<scanner::ActiveScan>, String> {
+    Ok(scanner::active_scans())
+}
+
 #[tauri::command]
-async fn build_inverted_index(files: Vec<scanner::FileMeta>, opts: indexer::IndexOptions) -> Result<(), String> {
+async fn build_inverted_index(files: Vec<scanner::FileMeta>, opts: indexer::IndexOptions, app: tauri::AppHandle) -> Result<(), String> {
     info!("build_inverted_index invoked: files={}, index_dir={}, content_parse={}",
         files.len(), opts.index_dir, opts.enable_content_parse);
-    indexer::build(files, opts).await.map_err(|e| e.to_string())
+    let index_dir = opts.index_dir.clone();
+    indexer::build(files, opts).await.map_err(|e| e.to_string())?;
+    pipeline::finish_rebuild(&index_dir, app.clone()).await;
+    search_alerts::check(&app, &index_dir).await;
+    Ok(())
 }
 
 #[tauri::command]
-async fn search_query(req: search::QueryRequest) -> Result<Vec<search::SearchResult>, String> {
+async fn search_query(req: search::QueryRequest) -> Result<search::SearchResponse, String> {
     info!("search_query: q='{}', index_dir='{}'", req.query, req.index_dir);
-    search::query(req).await.map_err(|e| e.to_string())
+    let res = search::query(req.clone()).await.map_err(|e| e.to_string())?;
+    // 记录检索历史，失败不影响检索结果
+    if let Err(e) = search_history::record(&req, res.total) { warn!("search_history: record failed: {}", e); }
+    Ok(res)
+}
+
+#[tauri::command]
+async fn list_search_history() -> Result<Vec<search_history::HistoryEntry>, String> {
+    Ok(search_history::list_history())
 }
 
 #[tauri::command]
-async fn detect_duplicates(paths: Vec<String>) -> Result<Vec<dedup::DupGroup>, String> {
+async fn clear_search_history() -> Result<(), String> {
+    warn!("clear_search_history invoked");
+    search_history::clear_history().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_saved_searches() -> Result<Vec<search_history::SavedSearch>, String> {
+    Ok(search_history::list_saved())
+}
+
+#[tauri::command]
+async fn save_search(name: String, req: search::QueryRequest) -> Result<search_history::SavedSearch, String> {
+    info!("save_search: name='{}', q='{}'", name, req.query);
+    search_history::save_search(&name, req).map_err(|e| e.to_string())
+}
+
+// 重新执行已保存的搜索（沿用保存时的完整请求，包括索引目录、过滤条件与排序）
+#[tauri::command]
+async fn run_saved_search(id: u64) -> Result<search::SearchResponse, String> {
+    let req = search_history::take_for_run(id).map_err(|e| e.to_string())?;
+    info!("run_saved_search: id={}, q='{}'", id, req.query);
+    let res = search::query(req.clone()).await.map_err(|e| e.to_string())?;
+    if let Err(e) = search_history::record(&req, res.total) { warn!("search_history: record failed: {}", e); }
+    Ok(res)
+}
+
+#[tauri::command]
+async fn rename_saved_search(id: u64, name: String) -> Result<(), String> {
+    search_history::rename_saved(id, &name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_saved_search(id: u64) -> Result<(), String> {
+    info!("delete_saved_search: id={}", id);
+    search_history::delete_saved(id).map_err(|e| e.to_string())?;
+    search_alerts::forget(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn pin_saved_search(id: u64, pinned: bool) -> Result<(), String> {
+    search_history::set_pinned(id, pinned).map_err(|e| e.to_string())
+}
+
+// 关注：开始时记录当前匹配作为基线，此后索引提交时提醒新出现的匹配
+#[tauri::command]
+async fn watch_saved_search(id: u64, watch: bool) -> Result<search_history::SavedSearch, String> {
+    info!("watch_saved_search: id={}, watch={}", id, watch);
+    let s = search_history::set_watch(id, watch).map_err(|e| e.to_string())?;
+    if watch {
+        search_alerts::watch(&s).await.map_err(|e| e.to_string())?;
+    } else {
+        search_alerts::forget(id).map_err(|e| e.to_string())?;
+    }
+    Ok(s)
+}
+
+#[tauri::command]
+async fn list_search_alerts() -> Result<Vec<search_alerts::SearchAlerts>, String> {
+    Ok(search_alerts::list())
+}
+
+// 标记已读：paths 为空时清空该搜索的全部未读
+#[tauri::command]
+async fn mark_search_alerts_read(id: u64, paths: Option<Vec<String>>) -> Result<(), String> {
+    search_alerts::mark_read(id, paths).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn reorder_saved_searches(ids: Vec<u64>) -> Result<(), String> {
+    search_history::reorder_saved(&ids).map_err(|e| e.to_string())
+}
+
+// 输入建议：最近的检索与词典补全；correct 为 true 时同时返回拼写纠正
+#[tauri::command]
+async fn suggest(req: suggest::SuggestRequest) -> Result<suggest::SuggestResponse, String> {
+    debug!("suggest: q='{}'", req.query);
+    suggest::suggest(&req).map_err(|e| e.to_string())
+}
+
+// 用户词典与同义词
+#[tauri::command]
+async fn get_dictionary() -> Result<dictionary::Dictionary, String> {
+    Ok(dictionary::get())
+}
+
+#[tauri::command]
+async fn set_synonyms(groups: Vec<Vec<String>>) -> Result<usize, String> {
+    info!("set_synonyms: groups={}", groups.len());
+    dictionary::set_synonyms(groups).map_err(|e| e.to_string())
+}
+
+// 添加或删除用户词后，切分发生变化的词所在的文档按新词典重写
+#[tauri::command]
+async fn add_user_words(words: Vec<dictionary::UserWord>) -> Result<dictionary::DictionaryUpdate, String> {
+    info!("add_user_words: {}", words.len());
+    dictionary::add_words(words).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_user_words(words: Vec<String>) -> Result<dictionary::DictionaryUpdate, String> {
+    info!("remove_user_words: {}", words.len());
+    dictionary::remove_words(words).await.map_err(|e| e.to_string())
+}
+
+// 导入与导出 jieba 用户词典格式（每行 "词 词频 词性"）
+#[tauri::command]
+async fn import_user_dictionary(text: String, replace: bool) -> Result<dictionary::DictionaryUpdate, String> {
+    info!("import_user_dictionary: bytes={}, replace={}", text.len(), replace);
+    dictionary::import_jieba(&text, replace).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn export_user_dictionary() -> Result<String, String> {
+    Ok(dictionary::export_jieba())
+}
+
+// 文件名即时匹配（子串/前缀），用于输入过程中的逐键检索
+#[tauri::command]
+async fn name_search(req: name_table::NameQuery) -> Result<search::SearchResponse, String> {
+    debug!("name_search: q='{}', mode={:?}", req.query, req.mode);
+    name_table::search(&req).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn detect_duplicates(paths: Vec<String>, raw_paths: Option<Vec<Option<String>>>) -> Result<Vec<dedup::DupGroup>, String> {
     info!("detect_duplicates: input_paths={}", paths.len());
-    dedup::detect(paths).await.map_err(|e| e.to_string())
+    dedup::detect(paths, raw_paths.unwrap_or_default()).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -184,12 +327,27 @@ async fn reset_config() -> Result<config::AppConfig, String> {
     config::reset_config().await.map_err(|e| e.to_string())
 }
 
+// 清空打开历史（frecency 排序与最近打开模式的数据来源）
+#[tauri::command]
+async fn clear_open_history() -> Result<(), String> {
+    warn!("clear_open_history invoked");
+    open_history::clear().map_err(|e| e.to_string())
+}
+
 // 打开所在位置（Windows: explorer /select, ；macOS: open -R；Linux: xdg-open 目录）
+// 文件与文件夹均在其父目录中定位；Linux 无法选中条目：文件夹直接打开自身，文件打开其所在目录
+// raw_path：非 UTF-8 路径的原始字节（十六进制），存在时以其为准
 #[tauri::command]
-fn open_location(path: String) -> Result<(), String> {
+fn open_location(path: String, raw_path: Option<String>) -> Result<(), String> {
+    // 记录打开历史（以索引中的路径为键），失败不影响打开
+    if let Err(e) = open_history::record_open(&path) { warn!("open_history: record failed: {}", e); }
+    let path = pathenc::resolve(&path, raw_path.as_deref());
     #[cfg(target_os = "windows")]
     {
-        Command::new("explorer").arg("/select,").arg(path).spawn().map_err(|e| e.to_string())?;
+        // explorer /select 需使用反斜杠，且文件夹路径不能以分隔符结尾
+        let p = path.as_os_str().to_string_lossy().replace('/', "\\");
+        let p = p.trim_end_matches('\\');
+        Command::new("explorer").arg("/select,").arg(p).spawn().map_err(|e| e.to_string())?;
         return Ok(());
     }
     #[cfg(target_os = "macos")]
@@ -199,9 +357,10 @@ fn open_location(path: String) -> Result<(), String> {
     }
     #[cfg(not(any(target_os = "windows", target_os = "macos")))]
     {
-        use std::path::Path;
-        let p = Path::new(&path);
-        let dir = if p.is_file() { p.parent().unwrap_or(p) } else { p };
+        let p = path.as_path();
+        // 使用 symlink_metadata：指向目录的符号链接按条目本身处理，定位到其所在目录
+        let is_dir = fs::symlink_metadata(p).map(|m| m.is_dir()).unwrap_or(false);
+        let dir = if is_dir { p } else { p.parent().unwrap_or(p) };
         Command::new("xdg-open").arg(dir).spawn().map_err(|e| e.to_string())?;
         return Ok(());
     }
@@ -211,30 +370,34 @@ fn main() {
     init_logging();
     tauri::Builder::default()
         .setup(|app| {
-            // 后台自动扫描（每天一次，系统空闲时）
+            // 后台自动扫描（各索引集合按自身调度频率，系统空闲时）
             let handle = app.handle();
             std::thread::spawn(move || {
                 let mut sys = System::new_all();
                 loop {
-                    // 读取配置与状态
+                    // 读取配置
                     let cfg = match tauri::async_runtime::block_on(config::read_config()) { Ok(c) => c, Err(_) => { std::thread::sleep(std::time::Duration::from_secs(60)); continue; } };
-                    let st = pipeline_state::load_state(&cfg.index_dir).unwrap_or_default();
-                    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
                     // 若未启用自动扫描，则跳过
                     if !cfg.auto_scan_enabled { std::thread::sleep(std::time::Duration::from_secs(60)); continue; }
-                    let need_run = (!st.completed) || (st.last_day.as_deref() != Some(&today));
-                    if need_run {
+                    // 各索引集合按自身的调度频率判断是否到期
+                    let due: Vec<config::IndexCollection> = cfg
+                        .all_collections()
+                        .into_iter()
+                        .filter(|c| pipeline_state::is_due(&c.index_dir, &c.schedule))
+                        .collect();
+                    if !due.is_empty() {
                         // 判断系统是否空闲（CPU < 20%）
                         sys.refresh_cpu();
                         let avg_cpu: f32 = sys.cpus().iter().map(|c| c.cpu_usage()).sum::<f32>() / (sys.cpus().len().max(1) as f32);
                         if avg_cpu < 20.0 {
-                            let _ = handle.emit_all("auto_scan_start", json!({"reason":"idle_daily"}));
-                            let _ = tauri::async_runtime::spawn(scan_and_index_pipeline_internal(
-                                cfg.scan_roots.clone(),
-                                cfg.exclude_patterns.clone(),
-                                indexer::IndexOptions { index_dir: cfg.index_dir.clone(), enable_content_parse: false },
-                                handle.clone(),
-                            ));
+                            for c in due {
+                                let _ = handle.emit_all("auto_scan_start", json!({"reason": "scheduled", "collection": c.name, "schedule": c.schedule}));
+                                let _ = tauri::async_runtime::spawn(scan_and_index_pipeline_internal(
+                                    cfg.collection_scan_options(&c),
+                                    indexer::IndexOptions { index_dir: c.index_dir.clone(), enable_content_parse: false },
+                                    handle.clone(),
+                                ));
+                            }
                         }
                     }
                     std::thread::sleep(std::time::Duration::from_secs(60));
@@ -245,17 +408,40 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             scan_paths,
             scan_paths_progress,
+            cancel_scan,
+            list_active_scans,
             build_inverted_index,
             build_inverted_index_progress,
             scan_and_index_pipeline,
             start_auto_scan_now,
             diagnostics::diagnostics_report,
             search_query,
+            suggest,
+            list_search_history,
+            clear_search_history,
+            list_saved_searches,
+            save_search,
+            run_saved_search,
+            rename_saved_search,
+            delete_saved_search,
+            pin_saved_search,
+            reorder_saved_searches,
+            watch_saved_search,
+            list_search_alerts,
+            mark_search_alerts_read,
+            get_dictionary,
+            set_synonyms,
+            add_user_words,
+            remove_user_words,
+            import_user_dictionary,
+            export_user_dictionary,
+            name_search,
             detect_duplicates,
             read_config,
             write_config,
             reset_config,
             open_location,
+            clear_open_history,
             delete_file_and_index
         ])
         .run(tauri::generate_context!())
@@ -269,39 +455,12 @@ async fn build_inverted_index_progress(
     opts: indexer::IndexOptions,
     window: tauri::Window,
 ) -> Result<(), String> {
-    use tantivy::{schema::*, schema::IndexRecordOption, Index, doc, Term, query::TermQuery, collector::TopDocs};
+    use tantivy::{schema::{IndexRecordOption, Value}, query::TermQuery, collector::TopDocs};
     use std::{fs, io::Read};
 
-    // 构建 schema（与 indexer.rs 保持一致，包含 summary 字段）
-    let mut schema_builder = Schema::builder();
-    // path 需可索引，支持 delete_term（upsert）
-    let f_path = schema_builder.add_text_field(
-        "path",
-        TextOptions::default()
-            .set_stored()
-            .set_indexing_options(TextFieldIndexing::default()),
-    );
-    let f_name = schema_builder.add_text_field(
-        "name",
-        TextOptions::default()
-            .set_stored()
-            .set_indexing_options(TextFieldIndexing::default()),
-    );
-    let f_ext = schema_builder.add_text_field("ext", TextOptions::default().set_stored());
-    let f_content = schema_builder.add_text_field(
-        "content",
-        TextOptions::default().set_indexing_options(TextFieldIndexing::default()),
-    );
-    let f_summary = schema_builder.add_text_field("summary", TextOptions::default().set_stored());
-    let f_size = schema_builder.add_u64_field("size", NumericOptions::default().set_stored());
-    let f_modified = schema_builder.add_i64_field("modified_ts", NumericOptions::default().set_stored());
-    let schema = schema_builder.build();
-
-    std::fs::create_dir_all(&opts.index_dir).map_err(|e| e.to_string())?;
-    let index = match Index::open_in_dir(&opts.index_dir) {
-        Ok(idx) => idx,
-        Err(_) => Index::create_in_dir(&opts.index_dir, schema.clone()).map_err(|e| e.to_string())?,
-    };
+    // schema 统一由 indexer::build_schema 定义；旧版本索引会自动重建
+    let index = indexer::open_or_create(&opts.index_dir).map_err(|e| e.to_string())?;
+    let f = indexer::IndexFields::from_schema(&index.schema()).map_err(|e| e.to_string())?;
     let mut writer: tantivy::IndexWriter<tantivy::TantivyDocument> = index
         .writer::<tantivy::TantivyDocument>(50_000_000)
         .map_err(|e| e.to_string())?; // 50MB
@@ -311,14 +470,14 @@ async fn build_inverted_index_progress(
     let searcher = reader.searcher();
     for (i, fm) in files.into_iter().enumerate() {
         // 若存在相同 path 的旧文档，比较时间与大小相同则跳过，否则删除旧文档
-        let term = Term::from_field_text(f_path, &fm.path);
+        let term = f.key_term(&fm.path, fm.raw_path.as_deref());
         let tq = TermQuery::new(term.clone(), IndexRecordOption::Basic);
         if let Ok(top_docs) = searcher.search(&tq, &TopDocs::with_limit(1)) {
             if !top_docs.is_empty() {
                 let (_score, addr) = top_docs[0];
                 if let Ok(old_doc) = searcher.doc::<tantivy::TantivyDocument>(addr) {
-                    let old_ts = old_doc.get_first(f_modified).and_then(|v| v.as_i64()).unwrap_or(0);
-                    let old_sz = old_doc.get_first(f_size).and_then(|v| v.as_u64()).unwrap_or(0);
+                    let old_ts = old_doc.get_first(f.modified).and_then(|v| v.as_i64()).unwrap_or(0);
+                    let old_sz = old_doc.get_first(f.size).and_then(|v| v.as_u64()).unwrap_or(0);
                     if old_ts == fm.modified_ts && old_sz == fm.size {
                         let _ = window.emit("index_progress", json!({
                             "current": i + 1,
@@ -333,32 +492,22 @@ async fn build_inverted_index_progress(
                 writer.delete_term(term);
             }
         }
-        let mut doc = doc!(
-            f_path => fm.path.clone(),
-            f_name => fm.file_name.clone(),
-            f_ext => fm.ext.clone(),
-            f_size => fm.size,
-            f_modified => fm.modified_ts,
-        );
-
-        if opts.enable_content_parse {
+        let mut doc = indexer::make_doc(&f, &fm, indexer::first_indexed_ts(&searcher, &f, &fm).unwrap_or_else(|| chrono::Local::now().timestamp()));
+
+        if opts.enable_content_parse && fm.kind == "file" {
             // 仅解析文本类，限制最大 1MB
             let ext = fm.ext.as_str();
             let text_like = ["txt", "md", "csv", "log", "json", "xml", "ini", "conf", "yaml", "yml"];
             if text_like.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
                 let max_bytes: usize = 1_000_000;
-                let path = std::path::Path::new(&fm.path);
+                let path = pathenc::resolve(&fm.path, fm.raw_path.as_deref());
                 if path.exists() && path.is_file() {
-                    if let Ok(mut file) = fs::File::open(path) {
+                    if let Ok(mut file) = fs::File::open(&path) {
                         let mut buf = Vec::with_capacity(max_bytes);
                         let mut handle = file.by_ref().take(max_bytes as u64);
                         if handle.read_to_end(&mut buf).is_ok() {
                             if let Ok(text) = String::from_utf8(buf) {
-                                doc.add_text(f_content, &text);
-                                let summary: String = text.chars().take(300).collect();
-                                if !summary.is_empty() {
-                                    doc.add_text(f_summary, &summary);
-                                }
+                                indexer::add_content(&f, &mut doc, &text);
                             }
                         }
                     }
@@ -377,38 +526,34 @@ async fn build_inverted_index_progress(
     }
 
     writer.commit().map_err(|e| e.to_string())?;
+    drop(writer);
+    indexer::apply_pending_deletes(&indexer::write_dir(&opts.index_dir)).map_err(|e| e.to_string())?;
     let _ = window.emit("index_done", json!({"ok": true}));
+    pipeline::finish_rebuild(&opts.index_dir, window.app_handle()).await;
+    search_alerts::check(&window.app_handle(), &opts.index_dir).await;
     Ok(())
 }
 // 删除文件并从索引中移除对应记录
 #[tauri::command]
-async fn delete_file_and_index(path: String, index_dir: String) -> Result<(), String> {
-    info!("delete_file_and_index: path={}, index_dir={}", path, index_dir);
-    // 先尝试删除文件（若失败则返回错误）
-    if let Err(e) = fs::remove_file(&path) {
+async fn delete_file_and_index(path: String, index_dir: String, raw_path: Option<String>) -> Result<(), String> {
+    info!("delete_file_and_index: path={}, index_dir={}, raw={}", path, index_dir, raw_path.is_some());
+    // 先尝试删除文件（若失败则返回错误）；非 UTF-8 路径按原始字节还原。
+    // 目录（含其下全部条目）不在此删除，避免误删整棵目录树；指向目录的符号链接按链接本身删除
+    let real = pathenc::resolve(&path, raw_path.as_deref());
+    if fs::symlink_metadata(&real).map(|m| m.is_dir()).unwrap_or(false) {
+        return Err(format!("不支持删除文件夹，请在文件管理器中操作: {}", path));
+    }
+    if let Err(e) = fs::remove_file(&real) {
         return Err(format!("删除文件失败: {}", e));
     }
 
-    // 从索引中删除对应文档（若索引不存在则忽略）
-    match Index::open_in_dir(&index_dir) {
-        Ok(index) => {
-            let schema = index.schema();
-            // Tantivy Schema::get_field returns a Result in current version; map error to String
-            let f_path = schema
-                .get_field("path")
-                .map_err(|_| "schema missing field 'path'".to_string())?;
-            let mut writer: tantivy::IndexWriter<tantivy::TantivyDocument> =
-                index.writer::<tantivy::TantivyDocument>(10_000_000)
-                    .map_err(|e| e.to_string())?; // 10MB
-            let term = Term::from_field_text(f_path, &path);
-            writer.delete_term(term);
-            writer.commit().map_err(|e| e.to_string())?;
-            info!("index record deleted for path: {}", path);
-            Ok(())
-        }
-        Err(_) => {
-            warn!("index not found at {}; skipping index deletion", index_dir);
-            Ok(())
-        }
+    // 从索引中删除对应文档（若索引不存在则忽略）；按新版本重建期间同时从重建中的索引删除，
+    // 避免替换后记录重新出现
+    let mut dirs = vec![index_dir.clone()];
+    if indexer::is_rebuilding(&index_dir) { dirs.push(indexer::rebuild_dir(&index_dir)); }
+    for dir in dirs {
+        indexer::delete_entry(&dir, &path, raw_path.as_deref()).map_err(|e| e.to_string())?;
     }
+    info!("index record deleted for path: {}", path);
+    Ok(())
 }
\ No newline at end of file
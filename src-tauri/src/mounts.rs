@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use log::warn;
+
+// 挂载点信息（来自 /proc/self/mountinfo，仅 Linux）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountEntry {
+    pub mount_point: String,
+    pub fs_type: String,
+    pub source: String,
+    // 分类："disk" | "pseudo" | "tmpfs" | "overlay" | "fuse" | "network"
+    pub kind: String,
+}
+
+// 默认跳过的挂载分类：伪文件系统、内存盘、容器叠加层与 FUSE（fuseblk 视为普通磁盘）
+pub fn default_skip_kinds() -> Vec<String> {
+    vec!["pseudo".into(), "tmpfs".into(), "overlay".into(), "fuse".into()]
+}
+
+// 按文件系统类型分类
+pub fn classify(fs_type: &str) -> &'static str {
+    const PSEUDO: &[&str] = &[
+        "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs", "debugfs",
+        "tracefs", "pstore", "bpf", "mqueue", "hugetlbfs", "configfs", "fusectl", "binfmt_misc",
+        "autofs", "rpc_pipefs", "nsfs", "efivarfs", "selinuxfs",
+    ];
+    const NETWORK: &[&str] = &["nfs", "nfs4", "cifs", "smb3", "smbfs", "9p", "afs", "ceph", "glusterfs"];
+    match fs_type {
+        t if PSEUDO.contains(&t) => "pseudo",
+        "tmpfs" | "ramfs" => "tmpfs",
+        "overlay" | "aufs" | "squashfs" => "overlay",
+        "fuseblk" => "disk",
+        t if t == "fuse" || t.starts_with("fuse.") => "fuse",
+        t if NETWORK.contains(&t) => "network",
+        _ => "disk",
+    }
+}
+
+// 枚举当前挂载点；非 Linux 平台返回空列表
+pub fn list_mounts() -> Vec<MountEntry> {
+    #[cfg(target_os = "linux")]
+    {
+        match std::fs::read_to_string("/proc/self/mountinfo") {
+            Ok(s) => parse_mountinfo(&s),
+            Err(e) => { warn!("read /proc/self/mountinfo failed: {}", e); Vec::new() }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+// mountinfo 每行格式：
+// 36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+// 第 5 列为挂载点，"-" 之后依次为文件系统类型与挂载源
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_mountinfo(s: &str) -> Vec<MountEntry> {
+    let mut out = Vec::new();
+    for line in s.lines() {
+        let fields: Vec<&str> = line.split(' ').collect();
+        if fields.len() < 7 { continue; }
+        let sep = match fields.iter().skip(6).position(|f| *f == "-") { Some(i) => i + 6, None => continue };
+        let fs_type = match fields.get(sep + 1) { Some(t) => *t, None => continue };
+        let source = fields.get(sep + 2).copied().unwrap_or("");
+        out.push(MountEntry {
+            mount_point: unescape(fields[4]),
+            fs_type: fs_type.to_string(),
+            source: unescape(source),
+            kind: classify(fs_type).to_string(),
+        });
+    }
+    out
+}
+
+// mountinfo 中空格、制表符、换行与反斜杠以 \ooo 八进制转义
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn unescape(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'\\' && i + 3 < b.len() && b[i + 1..i + 4].iter().all(|c| (b'0'..=b'7').contains(c)) {
+            out.push((b[i + 1] - b'0') * 64 + (b[i + 2] - b'0') * 8 + (b[i + 3] - b'0'));
+            i += 4;
+        } else {
+            out.push(b[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+// 需跳过的挂载点（分类位于 skip_kinds 中）；根目录 "/" 永不跳过
+pub fn skipped_mount_points(skip_kinds: &[String]) -> Vec<PathBuf> {
+    list_mounts()
+        .into_iter()
+        .filter(|m| m.mount_point != "/" && skip_kinds.iter().any(|k| k.eq_ignore_ascii_case(&m.kind)))
+        .map(|m| PathBuf::from(m.mount_point))
+        .collect()
+}
+
+// 本地磁盘挂载点（不含根目录 "/"，只保留目录）：Linux 默认扫描根目录，
+// 以及 one_file_system 时需单独扫描的子挂载点（如 /home、/data、/boot）
+pub fn local_disk_mounts() -> Vec<String> {
+    let mut out: Vec<String> = list_mounts()
+        .into_iter()
+        .filter(|m| m.kind == "disk" && m.mount_point != "/" && Path::new(&m.mount_point).is_dir())
+        .map(|m| m.mount_point)
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+// 实际扫描的根目录：one_file_system 时遍历不跨越挂载点，将位于某个根目录之下的本地磁盘挂载点追加为根目录；
+// 否则遍历会进入子挂载点，去掉位于其他根目录之下的根目录，避免重复扫描
+pub fn expand_roots(roots: &[String], disks: &[String], one_file_system: bool) -> Vec<String> {
+    let under = |p: &str, root: &str| p != root && Path::new(p).starts_with(root);
+    let mut out: Vec<String> = Vec::new();
+    for r in roots {
+        if !out.contains(r) { out.push(r.clone()); }
+    }
+    if one_file_system {
+        for d in disks {
+            if !out.contains(d) && roots.iter().any(|r| under(d, r)) { out.push(d.clone()); }
+        }
+    } else {
+        let all = out.clone();
+        out.retain(|r| !all.iter().any(|other| under(r, other)));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw,errors=remount-ro
+23 22 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw
+24 22 0:22 / /sys rw,nosuid,nodev,noexec,relatime shared:2 - sysfs sysfs rw
+25 22 0:5 / /dev rw,nosuid,relatime shared:3 - devtmpfs udev rw,size=8048220k
+26 22 0:24 / /run rw,nosuid,nodev,noexec,relatime shared:4 - tmpfs tmpfs rw,size=1615776k
+40 22 8:3 / /home rw,relatime shared:30 - ext4 /dev/sda3 rw
+41 22 8:17 / /mnt/My\\040Disk rw,relatime shared:31 - fuseblk /dev/sdb1 rw,user_id=0
+42 22 0:45 / /mnt/nas rw,relatime shared:32 - nfs4 192.168.1.2:/export\\134share rw,vers=4.2
+43 26 0:46 / /run/user/1000/gvfs rw,nosuid,nodev,relatime shared:33 - fuse.gvfsd-fuse gvfsd-fuse rw
+44 22 0:47 / /var/lib/docker/overlay2/abc/merged rw,relatime - overlay overlay rw,lowerdir=/a
+45 22 7:0 / /snap/core/1 ro,nodev,relatime shared:34 - squashfs /dev/loop0 ro
+46 22 0:48 / /mnt/opt rw master:1 shared:35 - xfs /dev/sdc1 rw
+bad line
+47 22 0:49 / /broken rw no-separator here
+";
+
+    #[test]
+    fn parses_mount_points_and_sources() {
+        let m = parse_mountinfo(MOUNTINFO);
+        assert_eq!(m.len(), 12);
+        assert_eq!(m[0].mount_point, "/");
+        assert_eq!(m[0].fs_type, "ext4");
+        assert_eq!(m[0].source, "/dev/sda2");
+        // 可选字段数量不定，按 "-" 定位文件系统类型
+        let opt = m.iter().find(|e| e.mount_point == "/mnt/opt").unwrap();
+        assert_eq!((opt.fs_type.as_str(), opt.source.as_str()), ("xfs", "/dev/sdc1"));
+    }
+
+    #[test]
+    fn unescapes_octal_sequences() {
+        let m = parse_mountinfo(MOUNTINFO);
+        assert!(m.iter().any(|e| e.mount_point == "/mnt/My Disk" && e.kind == "disk"));
+        assert!(m.iter().any(|e| e.source == "192.168.1.2:/export\\share"));
+        assert_eq!(unescape("a\\011b\\012c"), "a\tb\nc");
+        // 不完整或非八进制的转义原样保留
+        assert_eq!(unescape("x\\04"), "x\\04");
+        assert_eq!(unescape("x\\089"), "x\\089");
+    }
+
+    #[test]
+    fn classifies_filesystem_types() {
+        let kind = |mp: &str| parse_mountinfo(MOUNTINFO).into_iter().find(|e| e.mount_point == mp).unwrap().kind;
+        assert_eq!(kind("/proc"), "pseudo");
+        assert_eq!(kind("/sys"), "pseudo");
+        assert_eq!(kind("/dev"), "pseudo");
+        assert_eq!(kind("/run"), "tmpfs");
+        assert_eq!(kind("/home"), "disk");
+        assert_eq!(kind("/mnt/nas"), "network");
+        assert_eq!(kind("/run/user/1000/gvfs"), "fuse");
+        assert_eq!(kind("/var/lib/docker/overlay2/abc/merged"), "overlay");
+        assert_eq!(kind("/snap/core/1"), "overlay");
+        assert_eq!(classify("fuse"), "fuse");
+        assert_eq!(classify("fuseblk"), "disk");
+        assert_eq!(classify("cgroup2"), "pseudo");
+        assert_eq!(classify("btrfs"), "disk");
+    }
+
+    #[test]
+    fn expands_roots_with_nested_disk_mounts() {
+        let s = |v: &[&str]| v.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let disks = s(&["/boot", "/data", "/home", "/homework"]);
+        assert_eq!(expand_roots(&s(&["/"]), &disks, true), s(&["/", "/boot", "/data", "/home", "/homework"]));
+        // 只追加位于根目录之下的挂载点（按路径组件判断，/homework 不在 /home 之下）
+        assert_eq!(expand_roots(&s(&["/home"]), &disks, true), s(&["/home"]));
+        // 遍历会进入子挂载点时去掉嵌套的根目录
+        assert_eq!(expand_roots(&s(&["/", "/home", "/home"]), &disks, false), s(&["/"]));
+        assert_eq!(expand_roots(&s(&["/home", "/homework"]), &disks, false), s(&["/home", "/homework"]));
+    }
+}
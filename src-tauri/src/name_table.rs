@@ -0,0 +1,510 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tantivy::columnar::{Column, StrColumn};
+use tantivy::schema::{IndexRecordOption, Value};
+use tantivy::{DocAddress, DocId, DocSet, Searcher, SegmentReader, TantivyDocument, Term, TERMINATED};
+use log::{info, warn};
+use regex::{Regex, RegexBuilder};
+
+use crate::indexer;
+use crate::open_history::{self, OpenRecord};
+use crate::search::{self, QueryRequest, SearchFilters, SearchResponse, SearchResult};
+use crate::tokenizer;
+
+// 文件名即时匹配：从索引的 fast 列加载名称表，段内去重后的小写名称以 '\n' 分隔拼接为一段文本，
+// 子串查找直接在整段文本上进行（百万级条目约数十 MB，单次扫描为毫秒级）。
+// 名称表只保存名称文本与文档地址，过滤条件按 fast 列判断，结果页的条目信息从文档存储读取
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameQuery {
+    pub query: String,
+    pub index_dir: String,
+    // 匹配方式："substring"（默认，名称任意位置包含）| "prefix"（名称以查询开头）
+    #[serde(default)]
+    pub mode: Option<String>,
+    pub filters: Option<SearchFilters>,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+// 索引变化后名称表的最短重建间隔：扫描期间索引持续提交，避免频繁重建
+const REFRESH_MIN_INTERVAL: Duration = Duration::from_secs(30);
+
+// 条目类型（kind 字段不是 fast 列，构建时按倒排表逐段标记）
+const KIND_FILE: u8 = 0;
+const KIND_DIR: u8 = 1;
+const KIND_SYMLINK: u8 = 2;
+const KINDS: [(&str, u8); 3] = [("file", KIND_FILE), ("dir", KIND_DIR), ("symlink", KIND_SYMLINK)];
+
+pub struct NameTable {
+    opstamp: u64,
+    built_at: Instant,
+    // 构建时的 searcher：保持各段可用，结果页从中读取文档
+    searcher: Searcher,
+    fields: indexer::IndexFields,
+    // 小写名称拼接文本及每个名称在其中的起始偏移（末尾追加总长度作为哨兵）
+    haystack: String,
+    starts: Vec<usize>,
+    // 每个名称对应的文档：docs[doc_starts[i]..doc_starts[i + 1]]
+    doc_starts: Vec<u32>,
+    docs: Vec<DocAddress>,
+    // 各段内每个文档的条目类型（按段序号、文档号索引）
+    kinds: Vec<Vec<u8>>,
+}
+
+static TABLES: Lazy<Mutex<HashMap<String, Arc<NameTable>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 正在后台构建名称表的索引目录
+static LOADING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+// 获取索引对应的名称表：尚未加载时同步构建；索引有新提交且距上次构建超过 REFRESH_MIN_INTERVAL 时
+// 在后台重建，完成后整体替换，期间继续使用旧表
+pub fn get(index_dir: &str) -> Result<Arc<NameTable>> {
+    let open = indexer::open_cached(index_dir)?;
+    if let Some(t) = cached(index_dir, &open)? { return Ok(t); }
+    build(index_dir, &open)
+}
+
+// 不等待加载：名称表尚未加载时在后台开始构建并返回 None
+pub fn get_ready(index_dir: &str) -> Result<Option<Arc<NameTable>>> {
+    let open = indexer::open_cached(index_dir)?;
+    let table = cached(index_dir, &open)?;
+    if table.is_none() { refresh_in_background(index_dir, open); }
+    Ok(table)
+}
+
+fn cached(index_dir: &str, open: &Arc<indexer::OpenIndex>) -> Result<Option<Arc<NameTable>>> {
+    let Some(t) = TABLES.lock().get(index_dir).cloned() else { return Ok(None) };
+    if t.opstamp != open.index.load_metas()?.opstamp && t.built_at.elapsed() >= REFRESH_MIN_INTERVAL {
+        refresh_in_background(index_dir, open.clone());
+    }
+    Ok(Some(t))
+}
+
+fn refresh_in_background(index_dir: &str, open: Arc<indexer::OpenIndex>) {
+    if !LOADING.lock().insert(index_dir.to_string()) { return; }
+    let dir = index_dir.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = build(&dir, &open) { warn!("name_table rebuild failed for {}: {}", dir, e); }
+        LOADING.lock().remove(&dir);
+    });
+}
+
+fn build(index_dir: &str, open: &indexer::OpenIndex) -> Result<Arc<NameTable>> {
+    let opstamp = open.index.load_metas()?.opstamp;
+    // reader 在提交后延迟重新加载，这里立即刷新，使名称表与 opstamp 对应的提交一致
+    open.reader.reload()?;
+    let table = Arc::new(NameTable::load(open, opstamp)?);
+    TABLES.lock().insert(index_dir.to_string(), table.clone());
+    Ok(table)
+}
+
+// 单个段上按 fast 列判断过滤条件（与全文检索的过滤条件语义一致）
+struct SegmentFilter {
+    exts: Option<(Option<StrColumn>, HashSet<u64>)>,
+    size: Option<Column<u64>>,
+    modified: Option<Column<i64>>,
+    // 路径前缀：path_tree 列包含每个祖先目录及完整路径，前缀的序号出现在其中即位于该目录下
+    prefix: Option<(Option<StrColumn>, Option<u64>)>,
+}
+
+impl SegmentFilter {
+    fn new(seg: &SegmentReader, flt: &SearchFilters) -> Self {
+        let ff = seg.fast_fields();
+        let exts = flt.ext.as_ref().and_then(|exts| {
+            let exts: Vec<String> = exts.iter().map(|x| x.trim().trim_start_matches('.').to_lowercase()).filter(|x| !x.is_empty()).collect();
+            if exts.is_empty() { return None; }
+            let col = ff.str("ext").ok().flatten();
+            let ords = col.as_ref().map(|c| exts.iter().filter_map(|x| c.dictionary().term_ord(x.as_bytes()).ok().flatten()).collect()).unwrap_or_default();
+            Some((col, ords))
+        });
+        let size = (flt.min_size.is_some() || flt.max_size.is_some()).then(|| ff.u64("size").ok()).flatten();
+        let modified = flt.time_range.is_some().then(|| ff.i64("modified_ts").ok()).flatten();
+        let prefix = flt.path_prefix.as_deref().map(tokenizer::normalize_path).filter(|p| !p.is_empty()).map(|p| {
+            let col = ff.str("path_tree").ok().flatten();
+            let ord = col.as_ref().and_then(|c| c.dictionary().term_ord(p.as_bytes()).ok().flatten());
+            (col, ord)
+        });
+        SegmentFilter { exts, size, modified, prefix }
+    }
+
+    fn matches(&self, flt: &SearchFilters, doc: DocId, kind: u8) -> bool {
+        if let Some((col, ords)) = &self.exts {
+            match col.as_ref().and_then(|c| c.term_ords(doc).next()) {
+                Some(ord) if ords.contains(&ord) => {}
+                _ => return false,
+            }
+        }
+        let size = self.size.as_ref().and_then(|c| c.first(doc)).unwrap_or(0);
+        if flt.min_size.is_some_and(|min| size < min) || flt.max_size.is_some_and(|max| size > max) { return false; }
+        if let Some(k) = flt.kind.as_deref().filter(|k| !k.is_empty()) {
+            if !KINDS.iter().any(|(name, code)| k.eq_ignore_ascii_case(name) && *code == kind) { return false; }
+        }
+        if let Some((from, to)) = flt.time_range {
+            let ts = self.modified.as_ref().and_then(|c| c.first(doc)).unwrap_or(0);
+            if from.is_some_and(|f| ts < f) || to.is_some_and(|t| ts > t) { return false; }
+        }
+        if let Some((col, ord)) = &self.prefix {
+            let (Some(col), Some(ord)) = (col, ord) else { return false };
+            if !col.term_ords(doc).any(|o| o == *ord) { return false; }
+        }
+        true
+    }
+}
+
+// 查找时按段缓存的过滤器与打开历史（path 列序号 → frecency）
+struct SegmentState {
+    filter: Option<SegmentFilter>,
+    paths: Option<StrColumn>,
+    frecency: HashMap<u64, u32>,
+}
+
+struct SegmentContext<'a> {
+    filters: Option<&'a SearchFilters>,
+    history: &'a [(String, u32)],
+    segments: HashMap<u32, SegmentState>,
+}
+
+impl<'a> SegmentContext<'a> {
+    fn new(filters: Option<&'a SearchFilters>, history: &'a [(String, u32)]) -> Self {
+        SegmentContext { filters, history, segments: HashMap::new() }
+    }
+
+    // 条目满足过滤条件时返回其 frecency
+    fn check(&mut self, searcher: &Searcher, addr: DocAddress, kind: u8) -> Option<u32> {
+        let (filters, history) = (self.filters, self.history);
+        let st = self.segments.entry(addr.segment_ord).or_insert_with(|| {
+            let seg = searcher.segment_reader(addr.segment_ord);
+            let (paths, frecency) = if history.is_empty() { (None, HashMap::new()) } else { search::history_ords(seg, history) };
+            SegmentState { filter: filters.map(|f| SegmentFilter::new(seg, f)), paths, frecency }
+        });
+        if let (Some(sf), Some(f)) = (st.filter.as_ref(), filters) {
+            if !sf.matches(f, addr.doc_id, kind) { return None; }
+        }
+        Some(st.paths.as_ref().and_then(|c| c.term_ords(addr.doc_id).next()).and_then(|o| st.frecency.get(&o)).copied().unwrap_or(0))
+    }
+}
+
+// 按打开历史计算各路径的 frecency（取整，用作排序键）
+fn frecency_list(history: &HashMap<String, OpenRecord>) -> Vec<(String, u32)> {
+    let now = chrono::Local::now().timestamp();
+    history.iter().map(|(path, rec)| (path.clone(), open_history::frecency(rec, now) as u32)).collect()
+}
+
+impl NameTable {
+    fn load(open: &indexer::OpenIndex, opstamp: u64) -> Result<Self> {
+        let started = Instant::now();
+        let f = open.fields;
+        let searcher = open.reader.searcher();
+        let mut haystack = String::new();
+        let (mut starts, mut doc_starts, mut docs, mut kinds) = (Vec::new(), vec![0u32], Vec::new(), Vec::new());
+        for (seg_ord, seg) in searcher.segment_readers().iter().enumerate() {
+            let max_doc = seg.max_doc();
+            // 条目类型：默认为文件，按 kind 字段的倒排表标记文件夹与符号链接
+            let mut seg_kinds = vec![KIND_FILE; max_doc as usize];
+            let inverted = seg.inverted_index(f.kind)?;
+            for (name, code) in KINDS.iter().filter(|(_, code)| *code != KIND_FILE) {
+                if let Some(mut postings) = inverted.read_postings(&Term::from_field_text(f.kind, name), IndexRecordOption::Basic)? {
+                    while postings.doc() != TERMINATED {
+                        seg_kinds[postings.doc() as usize] = *code;
+                        postings.advance();
+                    }
+                }
+            }
+            kinds.push(seg_kinds);
+            let Some(col) = seg.fast_fields().str("name")? else { continue };
+            // 按名称序号分组文档（两遍：先计数，再按偏移填入）
+            let mut counts = vec![0u32; col.num_terms()];
+            let alive = |doc: DocId| seg.alive_bitset().map(|b| b.is_alive(doc)).unwrap_or(true);
+            for doc in (0..max_doc).filter(|&d| alive(d)) {
+                if let Some(ord) = col.term_ords(doc).next() { counts[ord as usize] += 1; }
+            }
+            let base = docs.len() as u32;
+            let mut offsets = Vec::with_capacity(counts.len());
+            let mut acc = base;
+            for c in &counts {
+                offsets.push(acc);
+                acc += c;
+            }
+            docs.resize(acc as usize, DocAddress::new(0, 0));
+            for doc in (0..max_doc).filter(|&d| alive(d)) {
+                let Some(ord) = col.term_ords(doc).next() else { continue };
+                let at = offsets[ord as usize] as usize;
+                docs[at] = DocAddress::new(seg_ord as u32, doc);
+                offsets[ord as usize] += 1;
+            }
+            // 名称按序号依次加入（只保留仍有文档的名称）
+            let mut stream = col.dictionary().stream()?;
+            while stream.advance() {
+                let ord = stream.term_ord() as usize;
+                if counts[ord] == 0 { continue; }
+                starts.push(haystack.len());
+                haystack.push_str(&String::from_utf8_lossy(stream.key()).replace('\n', " "));
+                haystack.push('\n');
+                doc_starts.push(offsets[ord]);
+            }
+        }
+        starts.push(haystack.len());
+        info!(
+            "name_table loaded: names={}, entries={}, bytes={}, elapsed_ms={}",
+            starts.len() - 1, docs.len(), haystack.len(), started.elapsed().as_millis()
+        );
+        Ok(NameTable { opstamp, built_at: Instant::now(), searcher, fields: f, haystack, starts, doc_starts, docs, kinds })
+    }
+
+    fn kind_of(&self, addr: DocAddress) -> u8 {
+        self.kinds[addr.segment_ord as usize][addr.doc_id as usize]
+    }
+
+    fn name_docs(&self, name_idx: usize) -> std::ops::Range<usize> {
+        self.doc_starts[name_idx] as usize..self.doc_starts[name_idx + 1] as usize
+    }
+
+    // 查找名称匹配的条目：多个空格分隔的词需全部包含（第一个词决定扫描，其余词逐名称校验）；
+    // 返回命中总数及前 top_k 个条目在 docs 中的下标（常用的条目按 frecency 优先，
+    // 其次名称以查询开头的优先，再次名称更短的优先）。只保留前 top_k 个，不对全部命中排序
+    fn find(&self, query: &str, prefix_only: bool, filters: Option<&SearchFilters>, history: &[(String, u32)], top_k: usize) -> (usize, Vec<usize>) {
+        let lower = query.to_lowercase();
+        let mut words = lower.split_whitespace();
+        let first = match words.next() { Some(w) => w, None => return (0, Vec::new()) };
+        let rest: Vec<&str> = words.collect();
+        let mut ctx = SegmentContext::new(filters, history);
+        let mut top: BinaryHeap<(Reverse<u32>, bool, usize, usize)> = BinaryHeap::with_capacity(top_k + 1);
+        let mut total = 0;
+        let mut pos = 0;
+        while let Some(found) = self.haystack[pos..].find(first) {
+            let at = pos + found;
+            let idx = self.starts.partition_point(|&s| s <= at) - 1;
+            let (start, end) = (self.starts[idx], self.starts[idx + 1] - 1);
+            pos = end + 1;
+            let is_prefix = at == start;
+            if prefix_only && !is_prefix { continue; }
+            let name = &self.haystack[start..end];
+            if !rest.iter().all(|w| name.contains(w)) { continue; }
+            for i in self.name_docs(idx) {
+                let Some(frecency) = ctx.check(&self.searcher, self.docs[i], self.kind_of(self.docs[i])) else { continue };
+                total += 1;
+                top.push((Reverse(frecency), !is_prefix, name.len(), i));
+                if top.len() > top_k { top.pop(); }
+            }
+        }
+        (total, top.into_sorted_vec().into_iter().map(|(_, _, _, i)| i).collect())
+    }
+
+    // 正则匹配小写名称（正则不区分大小写）；返回命中的文档及是否超时
+    fn regex_names(&self, re: &Regex, ctx: &mut SegmentContext, started: Instant, budget: Duration) -> (Vec<DocAddress>, bool) {
+        let mut hits = Vec::new();
+        for idx in 0..self.starts.len() - 1 {
+            if idx % REGEX_CHECK_EVERY == 0 && started.elapsed() > budget { return (hits, true); }
+            if !re.is_match(&self.haystack[self.starts[idx]..self.starts[idx + 1] - 1]) { continue; }
+            for i in self.name_docs(idx) {
+                let addr = self.docs[i];
+                if ctx.check(&self.searcher, addr, self.kind_of(addr)).is_some() { hits.push(addr); }
+            }
+        }
+        (hits, false)
+    }
+
+    // 正则匹配完整路径：逐段顺序读取 path 列的词典（Windows 路径分隔符统一为 '/' 后匹配）
+    fn regex_paths(&self, re: &Regex, ctx: &mut SegmentContext, started: Instant, budget: Duration) -> Result<(Vec<DocAddress>, bool)> {
+        let mut hits = Vec::new();
+        let mut checked = 0usize;
+        for (seg_ord, seg) in self.searcher.segment_readers().iter().enumerate() {
+            let Some(col) = seg.fast_fields().str("path")? else { continue };
+            let mut doc_of_ord = vec![DocId::MAX; col.num_terms()];
+            for doc in 0..seg.max_doc() {
+                if seg.alive_bitset().is_some_and(|b| !b.is_alive(doc)) { continue; }
+                if let Some(ord) = col.term_ords(doc).next() { doc_of_ord[ord as usize] = doc; }
+            }
+            let mut stream = col.dictionary().stream()?;
+            while stream.advance() {
+                checked += 1;
+                if checked % REGEX_CHECK_EVERY == 0 && started.elapsed() > budget { return Ok((hits, true)); }
+                let doc = doc_of_ord[stream.term_ord() as usize];
+                if doc == DocId::MAX { continue; }
+                let path = String::from_utf8_lossy(stream.key());
+                #[cfg(target_os = "windows")]
+                let path = path.replace('\\', "/");
+                if !re.is_match(&path) { continue; }
+                let addr = DocAddress::new(seg_ord as u32, doc);
+                if ctx.check(&self.searcher, addr, self.kind_of(addr)).is_some() { hits.push(addr); }
+            }
+        }
+        Ok((hits, false))
+    }
+
+    fn result(&self, addr: DocAddress, score: f32, history: &HashMap<String, OpenRecord>) -> Result<SearchResult> {
+        let doc: TantivyDocument = self.searcher.doc(addr)?;
+        let f = self.fields;
+        let text = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let path = text(f.path);
+        let opened = history.get(&path);
+        Ok(SearchResult {
+            name: text(f.name),
+            ext: text(f.ext),
+            score,
+            size: doc.get_first(f.size).and_then(|v| v.as_u64()),
+            modified_ts: doc.get_first(f.modified).and_then(|v| v.as_i64()),
+            summary: None,
+            kind: doc.get_first(f.kind).and_then(|v| v.as_str()).unwrap_or("file").to_string(),
+            child_count: doc.get_first(f.child_count).and_then(|v| v.as_u64()),
+            raw_path: doc.get_first(f.raw_path).and_then(|v| v.as_bytes()).map(hex::encode),
+            snippet: None,
+            snippet_highlights: Vec::new(),
+            name_highlights: Vec::new(),
+            bm25: None,
+            hit_fields: None,
+            explanation: None,
+            collection: None,
+            open_count: opened.map(|r| r.count),
+            last_opened_ts: opened.map(|r| r.last_ts),
+            path,
+        })
+    }
+}
+
+// 名称高亮：各个词在小写名称中首次出现的位置（按起点排序）；大小写转换改变长度时不高亮
+fn name_highlights(name: &str, query: &str) -> Vec<(usize, usize)> {
+    let lower = name.to_lowercase();
+    if lower.len() != name.len() { return Vec::new(); }
+    let mut ranges: Vec<(usize, usize)> = query
+        .to_lowercase()
+        .split_whitespace()
+        .filter_map(|w| lower.find(w).map(|at| (at, at + w.len())))
+        .collect();
+    ranges.sort_unstable();
+    ranges.into_iter().map(|(s, e)| (name[..s].encode_utf16().count(), name[..e].encode_utf16().count())).collect()
+}
+
+pub fn search(req: &NameQuery) -> Result<SearchResponse> {
+    let started = Instant::now();
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let table = get(&req.index_dir)?;
+    let prefix_only = req.mode.as_deref().map(|m| m.eq_ignore_ascii_case("prefix")).unwrap_or(false);
+    let history = open_history::snapshot();
+    let (total, hits) = table.find(&req.query, prefix_only, req.filters.as_ref(), &frecency_list(&history), req.offset + limit);
+    let mut results = Vec::new();
+    for i in hits.into_iter().skip(req.offset) {
+        let mut r = table.result(table.docs[i], 1.0, &history)?;
+        r.name_highlights = name_highlights(&r.name, &req.query);
+        results.push(r);
+    }
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    Ok(SearchResponse { total, elapsed_ms, offset: req.offset, limit, fuzzy_applied: false, timed_out: false, results, analyzed_terms: None, facets: None, did_you_mean: None })
+}
+
+// 正则匹配的默认时间预算；每扫描 REGEX_CHECK_EVERY 条检查一次是否超时
+const DEFAULT_REGEX_TIMEOUT_MS: u64 = 2000;
+const REGEX_CHECK_EVERY: usize = 4096;
+
+// 正则检索：逐条匹配名称表中的文件名或完整路径
+pub fn regex_search(req: &QueryRequest) -> Result<SearchResponse> {
+    let started = Instant::now();
+    let limit = req.limit.unwrap_or(search::DEFAULT_LIMIT).clamp(1, search::MAX_LIMIT);
+    let re = RegexBuilder::new(&req.query)
+        .case_insensitive(true)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| anyhow::anyhow!("正则表达式无效：{}", e))?;
+    let on_path = req.regex_target.as_deref().map(|t| t.eq_ignore_ascii_case("path")).unwrap_or(false);
+    let budget = Duration::from_millis(req.regex_timeout_ms.unwrap_or(DEFAULT_REGEX_TIMEOUT_MS));
+    // 名称表尚未加载（加载耗时可能超过整个时间预算）时不等待，在后台加载并提示稍后重试
+    let Some(table) = get_ready(&req.index_dir)? else {
+        return Err(anyhow::anyhow!("文件名索引正在加载，请稍后重试"));
+    };
+    let history = open_history::snapshot();
+    let mut ctx = SegmentContext::new(req.filters.as_ref(), &[]);
+    let (hits, timed_out) = if on_path { table.regex_paths(&re, &mut ctx, started, budget)? } else { table.regex_names(&re, &mut ctx, started, budget) };
+    if timed_out { warn!("regex search timed out after {:?}: pattern='{}', matched={}", budget, req.query, hits.len()); }
+    let total = hits.len();
+    let results = hits.into_iter().skip(req.offset).take(limit).map(|addr| table.result(addr, 1.0, &history)).collect::<Result<Vec<_>>>()?;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("regex search done: pattern='{}', target={}, total={}, elapsed_ms={}", req.query, if on_path { "path" } else { "name" }, total, elapsed_ms);
+    Ok(SearchResponse { total, elapsed_ms, offset: req.offset, limit, fuzzy_applied: false, timed_out, results, analyzed_terms: None, facets: None, did_you_mean: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FileMeta;
+
+    fn entry(path: &str, kind: &str, size: u64, modified_ts: i64) -> FileMeta {
+        let name = path.rsplit('/').next().unwrap().to_string();
+        let ext = if kind == "file" { name.rsplit_once('.').map(|(_, e)| e.to_string()).unwrap_or_default() } else { String::new() };
+        FileMeta { path: path.into(), file_name: name, ext, size, modified_ts, kind: kind.into(), child_count: None, raw_path: None }
+    }
+
+    // 两次提交（两个段），第二次提交删除一个条目
+    fn temp_index() -> String {
+        let dir = std::env::temp_dir().join(format!("searchevery-test-names-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dir = dir.to_string_lossy().to_string();
+        let index = indexer::open_or_create(&dir).unwrap();
+        let f = indexer::IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(15_000_000).unwrap();
+        for e in [entry("/a/Report.txt", "file", 10, 100), entry("/b/report.txt", "file", 2000, 200), entry("/a/old_report.pdf", "file", 30, 300)] {
+            writer.add_document(indexer::make_doc(&f, &e, 0)).unwrap();
+        }
+        writer.commit().unwrap();
+        for e in [entry("/a/reports", "dir", 0, 400), entry("/c/annual report draft.docx", "file", 50, 500), entry("/gone/report.txt", "file", 1, 1)] {
+            writer.add_document(indexer::make_doc(&f, &e, 0)).unwrap();
+        }
+        writer.delete_term(f.key_term("/gone/report.txt", None));
+        writer.commit().unwrap();
+        dir
+    }
+
+    fn names(req: NameQuery) -> (usize, Vec<String>) {
+        let r = search(&req).unwrap();
+        (r.total, r.results.into_iter().map(|r| r.path).collect())
+    }
+
+    fn query(dir: &str, q: &str, filters: Option<SearchFilters>, limit: usize) -> NameQuery {
+        NameQuery { query: q.into(), index_dir: dir.into(), mode: None, filters, offset: 0, limit: Some(limit) }
+    }
+
+    #[test]
+    fn finds_ranks_and_filters_names() {
+        let dir = temp_index();
+        // 前缀匹配优先，其次名称更短；已删除的条目不出现
+        let (total, paths) = names(query(&dir, "report", None, 100));
+        assert_eq!(total, 5);
+        assert!(!paths.contains(&"/gone/report.txt".to_string()));
+        assert_eq!(&paths[3..], ["/a/old_report.pdf", "/c/annual report draft.docx"]);
+        // 只取前 k 个，总数不变
+        let (total, top) = names(query(&dir, "report", None, 2));
+        assert_eq!((total, top.len()), (5, 2));
+        assert_eq!(top, paths[..2]);
+        // 多个词需全部包含
+        assert_eq!(names(query(&dir, "report draft", None, 10)).1, ["/c/annual report draft.docx"]);
+        let prefix = NameQuery { mode: Some("prefix".into()), ..query(&dir, "report", None, 10) };
+        assert_eq!(names(prefix).0, 3);
+
+        let flt = |v: serde_json::Value| Some(serde_json::from_value::<SearchFilters>(v).unwrap());
+        assert_eq!(names(query(&dir, "report", flt(serde_json::json!({"kind": "dir"})), 10)).1, ["/a/reports"]);
+        assert_eq!(names(query(&dir, "report", flt(serde_json::json!({"ext": [".PDF"]})), 10)).1, ["/a/old_report.pdf"]);
+        assert_eq!(names(query(&dir, "report", flt(serde_json::json!({"min_size": 1000})), 10)).1, ["/b/report.txt"]);
+        assert_eq!(names(query(&dir, "report", flt(serde_json::json!({"time_range": [250, 450]})), 10)).0, 2);
+        assert_eq!(names(query(&dir, "report", flt(serde_json::json!({"path_prefix": "/a"})), 10)).0, 3);
+        assert_eq!(names(query(&dir, "report", flt(serde_json::json!({"path_prefix": "/nowhere"})), 10)).0, 0);
+
+        // 正则检索：名称表已加载后按名称或完整路径匹配
+        let regex = |q: &str, target: &str| {
+            let req: QueryRequest = serde_json::from_value(serde_json::json!({"query": q, "index_dir": dir, "regex": true, "regex_target": target})).unwrap();
+            regex_search(&req).unwrap().total
+        };
+        assert_eq!(regex("^report\\.txt$", "name"), 2);
+        assert_eq!(regex("^/a/", "path"), 3);
+        indexer::invalidate_cached(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use log::warn;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+// 打开历史：记录通过本应用打开的条目（路径、次数与最近若干次打开时间），
+// 用于按使用频率与新近程度（frecency）调整排序，以及 "最近打开" 检索模式。
+// 存放在应用配置目录，与 config.rs、pipeline_state.rs 一致
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenRecord {
+    pub count: u32,
+    pub last_ts: i64,
+    // 最近 MAX_SAMPLES 次打开时间（Unix 秒，旧在前）
+    #[serde(default)]
+    pub recent: Vec<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OpenHistory {
+    entries: HashMap<String, OpenRecord>,
+}
+
+// 每个条目保留的打开时间样本数，以及历史条目上限（超出时淘汰 frecency 最低的）
+const MAX_SAMPLES: usize = 10;
+const MAX_ENTRIES: usize = 5000;
+
+// 按距今天数分段的权重（参照浏览器地址栏的 frecency 算法）
+const AGE_WEIGHTS: &[(i64, f32)] = &[(4, 100.0), (14, 70.0), (31, 50.0), (90, 30.0)];
+const OLD_WEIGHT: f32 = 10.0;
+
+static HISTORY: Lazy<Mutex<Option<OpenHistory>>> = Lazy::new(|| Mutex::new(None));
+
+fn history_path() -> PathBuf {
+    tauri::api::path::app_config_dir(&tauri::Config::default())
+        .unwrap_or_else(|| PathBuf::from("./"))
+        .join("open_history.json")
+}
+
+fn load() -> OpenHistory {
+    let p = history_path();
+    if !p.exists() { return OpenHistory::default(); }
+    match fs::read_to_string(&p).map_err(anyhow::Error::from).and_then(|s| Ok(serde_json::from_str(&s)?)) {
+        Ok(h) => h,
+        Err(e) => {
+            warn!("open_history: failed to load {:?}: {}", p, e);
+            OpenHistory::default()
+        }
+    }
+}
+
+fn with_history<R>(f: impl FnOnce(&mut OpenHistory) -> R) -> R {
+    let mut guard = HISTORY.lock();
+    f(guard.get_or_insert_with(load))
+}
+
+fn save(h: &OpenHistory) -> Result<()> {
+    let p = history_path();
+    if let Some(dir) = p.parent() { fs::create_dir_all(dir)?; }
+    fs::write(p, serde_json::to_string(h)?)?;
+    Ok(())
+}
+
+// frecency：打开次数 × 最近若干次打开的平均时间权重；从未打开为 0
+pub fn frecency(rec: &OpenRecord, now: i64) -> f32 {
+    if rec.recent.is_empty() { return 0.0; }
+    let weight = |ts: i64| {
+        let days = (now - ts).max(0) / 86400;
+        AGE_WEIGHTS.iter().find(|(d, _)| days <= *d).map(|(_, w)| *w).unwrap_or(OLD_WEIGHT)
+    };
+    let avg = rec.recent.iter().map(|&ts| weight(ts)).sum::<f32>() / rec.recent.len() as f32;
+    rec.count as f32 * avg
+}
+
+pub fn record_open(path: &str) -> Result<()> {
+    let now = chrono::Local::now().timestamp();
+    with_history(|h| {
+        let rec = h.entries.entry(path.to_string()).or_default();
+        rec.count = rec.count.saturating_add(1);
+        rec.last_ts = now;
+        rec.recent.push(now);
+        if rec.recent.len() > MAX_SAMPLES { rec.recent.remove(0); }
+        if h.entries.len() > MAX_ENTRIES {
+            let mut scored: Vec<(f32, String)> = h.entries.iter().map(|(p, r)| (frecency(r, now), p.clone())).collect();
+            scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (_, p) in scored.into_iter().take(h.entries.len() - MAX_ENTRIES) { h.entries.remove(&p); }
+        }
+        save(h)
+    })
+}
+
+// 当前打开历史的快照（检索时使用，避免持锁期间访问索引）
+pub fn snapshot() -> HashMap<String, OpenRecord> {
+    with_history(|h| h.entries.clone())
+}
+
+pub fn clear() -> Result<()> {
+    with_history(|h| {
+        h.entries.clear();
+        save(h)
+    })
+}
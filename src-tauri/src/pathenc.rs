@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+// 非 UTF-8 路径的无损表示：
+// - 展示与检索统一使用 to_string_lossy 得到的字符串（无效字节替换为 U+FFFD）
+// - 同时保存原始字节（Unix 为 OsStr 字节，Windows 为 UTF-16 码元的小端字节），
+//   以十六进制字符串在前后端之间传递，用于打开、删除与哈希等需要真实路径的操作
+
+// 路径为合法 UTF-8 时返回 None；否则返回原始字节
+pub fn raw_bytes(path: &Path) -> Option<Vec<u8>> {
+    if path.to_str().is_some() { return None; }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(path.as_os_str().as_bytes().to_vec())
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        Some(path.as_os_str().encode_wide().flat_map(|u| u.to_le_bytes()).collect())
+    }
+}
+
+// 路径为合法 UTF-8 时返回 None；否则返回原始字节的十六进制编码
+pub fn raw_hex(path: &Path) -> Option<String> {
+    raw_bytes(path).map(hex::encode)
+}
+
+// 由原始字节还原路径
+pub fn from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(PathBuf::from(std::ffi::OsStr::from_bytes(bytes)))
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        if bytes.len() % 2 != 0 { return None; }
+        let wide: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        Some(PathBuf::from(std::ffi::OsString::from_wide(&wide)))
+    }
+}
+
+// 解析前端传回的路径：存在 raw_path 时以其为准，否则使用展示路径
+pub fn resolve(path: &str, raw_path: Option<&str>) -> PathBuf {
+    raw_path
+        .and_then(|h| hex::decode(h).ok())
+        .and_then(|b| from_bytes(&b))
+        .unwrap_or_else(|| PathBuf::from(path))
+}
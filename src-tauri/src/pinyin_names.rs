@@ -0,0 +1,88 @@
+use pinyin::ToPinyinMulti;
+use std::collections::BTreeSet;
+
+use crate::tokenizer;
+
+// 中文文件名的拼音变体：按 jieba 词切分后逐词生成全拼（zhoubao）与首字母（zb），
+// 连续的中文词另外整体生成一组（如 "年度周报" 同时产出 niandu、zhoubao 与 nianduzhoubao）。
+// 多音字按全部读音组合，组合数超过上限时其余字只取常用读音
+
+// 单个词最多保留的读音组合数
+const MAX_VARIANTS: usize = 8;
+
+pub struct PinyinVariants {
+    // 以空格分隔的全拼 token
+    pub full: String,
+    // 以空格分隔的首字母 token
+    pub initials: String,
+}
+
+fn has_han(s: &str) -> bool {
+    s.chars().any(|c| c.to_pinyin_multi().is_some())
+}
+
+// 名称不含汉字时返回 None
+pub fn variants(name: &str) -> Option<PinyinVariants> {
+    if !has_han(name) { return None; }
+    let mut full = BTreeSet::new();
+    let mut initials = BTreeSet::new();
+    let mut run: Vec<&str> = Vec::new();
+    let flush = |run: &mut Vec<&str>, full: &mut BTreeSet<String>, initials: &mut BTreeSet<String>| {
+        if run.len() > 1 {
+            let joined = run.concat();
+            for (f, i) in readings(&joined).into_iter().take(1) {
+                full.insert(f);
+                initials.insert(i);
+            }
+        }
+        run.clear();
+    };
+    for word in tokenizer::cut(name) {
+        if has_han(word) {
+            for (f, i) in readings(word) {
+                full.insert(f);
+                initials.insert(i);
+            }
+            run.push(word);
+        } else {
+            flush(&mut run, &mut full, &mut initials);
+        }
+    }
+    flush(&mut run, &mut full, &mut initials);
+    Some(PinyinVariants {
+        full: full.into_iter().collect::<Vec<_>>().join(" "),
+        initials: initials.into_iter().collect::<Vec<_>>().join(" "),
+    })
+}
+
+// 一个词的全部（受上限约束的）读音组合：(全拼, 首字母)；非汉字字符按小写原样保留
+fn readings(word: &str) -> Vec<(String, String)> {
+    let mut combos: Vec<(String, String)> = vec![(String::new(), String::new())];
+    for (c, multi) in word.chars().zip(word.to_pinyin_multi()) {
+        let options: Vec<(&str, &str)> = match multi {
+            Some(m) => {
+                // 不同声调的读音去掉声调后可能相同
+                let mut opts: Vec<(&str, &str)> = Vec::new();
+                for p in m {
+                    let o = (p.plain(), p.first_letter());
+                    if !opts.contains(&o) { opts.push(o); }
+                }
+                opts
+            }
+            None if c.is_alphanumeric() => {
+                let lower = c.to_lowercase().to_string();
+                combos.iter_mut().for_each(|(f, i)| { f.push_str(&lower); i.push_str(&lower); });
+                continue;
+            }
+            None => continue,
+        };
+        // 组合数已达上限时只取第一个（最常用）读音
+        let take = if combos.len() * options.len() > MAX_VARIANTS { 1 } else { options.len() };
+        combos = combos
+            .iter()
+            .flat_map(|(f, i)| options.iter().take(take).map(move |(pf, pi)| (format!("{}{}", f, pf), format!("{}{}", i, pi))))
+            .collect();
+    }
+    combos.retain(|(f, _)| !f.is_empty());
+    combos
+}
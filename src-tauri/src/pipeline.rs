@@ -9,7 +9,28 @@ use sysinfo::System;
 use tauri::{Window, AppHandle};
 use tauri::Manager; // for Window::app_handle and AppHandle::get_window
 
-use crate::{indexer, config, pipeline_state};
+use crate::{indexer, config, pipeline_state, scanner, pathenc, search_alerts, dictionary};
+
+// 扫描期间检查关注搜索的最短间隔（每个文档单独提交，不逐次检查）
+const ALERT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// 正在执行管道的索引目录：同一索引同时只能有一个 writer，重复触发（如调度与手动同时发生）时直接跳过
+static RUNNING: once_cell::sync::Lazy<parking_lot::Mutex<std::collections::HashSet<String>>> =
+    once_cell::sync::Lazy::new(Default::default);
+
+struct RunningGuard(String);
+
+impl RunningGuard {
+    fn acquire(index_dir: &str) -> Option<Self> {
+        RUNNING.lock().insert(index_dir.to_string()).then(|| RunningGuard(index_dir.to_string()))
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.lock().remove(&self.0);
+    }
+}
 
 // 合并扫描与索引：扫描到文件即投递到索引构建（多线程，资源感知）
 #[tauri::command]
@@ -19,36 +40,25 @@ pub async fn scan_and_index_pipeline(
     window: Window,
 ) -> Result<(), String> {
     let handle = window.app_handle();
-    scan_and_index_pipeline_internal(opts.roots, opts.exclude_patterns, index_opts, handle).await
+    scan_and_index_pipeline_internal(opts, index_opts, handle).await
 }
 
 pub async fn scan_and_index_pipeline_internal(
-    roots: Vec<String>,
-    exclude_patterns: Vec<String>,
+    opts: crate::ScanOptions,
     index_opts: indexer::IndexOptions,
     app: AppHandle,
 ) -> Result<(), String> {
-    use walkdir::WalkDir;
-    use tantivy::{schema::*, Index, doc, Term};
     use std::{fs, io::Read};
 
-    // 初始化 schema 与索引（path 可索引）
-    let mut schema_builder = Schema::builder();
-    let f_path = schema_builder.add_text_field("path", TextOptions::default().set_stored().set_indexing_options(TextFieldIndexing::default()));
-    let f_name = schema_builder.add_text_field("name", TextOptions::default().set_stored().set_indexing_options(TextFieldIndexing::default()));
-    let f_ext = schema_builder.add_text_field("ext", TextOptions::default().set_stored());
-    let f_content = schema_builder.add_text_field("content", TextOptions::default().set_indexing_options(TextFieldIndexing::default()));
-    let f_summary = schema_builder.add_text_field("summary", TextOptions::default().set_stored());
-    let f_size = schema_builder.add_u64_field("size", NumericOptions::default().set_stored());
-    let f_modified = schema_builder.add_i64_field("modified_ts", NumericOptions::default().set_stored());
-    let schema = schema_builder.build();
-
-    std::fs::create_dir_all(&index_opts.index_dir).map_err(|e| e.to_string())?;
-    let index = match Index::open_in_dir(&index_opts.index_dir) {
-        Ok(idx) => idx,
-        Err(_) => Index::create_in_dir(&index_opts.index_dir, schema.clone()).map_err(|e| e.to_string())?,
+    let Some(running) = RunningGuard::acquire(&index_opts.index_dir) else {
+        info!("pipeline already running for {}; skipped", index_opts.index_dir);
+        return Ok(());
     };
 
+    // 初始化索引（schema 统一由 indexer::build_schema 定义；旧版本索引会自动重建）
+    let index = indexer::open_or_create(&index_opts.index_dir).map_err(|e| e.to_string())?;
+    let fields = indexer::IndexFields::from_schema(&index.schema()).map_err(|e| e.to_string())?;
+
     // 资源感知的线程池：保留至少 2 个核心
     let cpu = num_cpus::get_physical().max(1);
     let threads = cpu.saturating_sub(2).max(1);
@@ -73,105 +83,150 @@ pub async fn scan_and_index_pipeline_internal(
         else { let _ = app.emit_all(name, payload); }
     };
 
-    let max_bytes_opt: Option<u64> = Some(500 * 1024 * 1024); // 500MB 上限
+    // 扫描开始时的索引快照：重写已有条目时沿用其首次写入时间
+    let prior = index.reader().map_err(|e| e.to_string())?.searcher();
+
     let mut scanned_files: usize = 0;
     let total_counter = Arc::new(AtomicUsize::new(0));
+    // 写入完成信号：每个任务持有一个 Sender，结束（含 panic 或未执行即被丢弃）时随之释放；
+    // 全部释放后接收端返回，不依赖计数
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
 
-    info!("pipeline start: roots={:?}, index_dir={}", roots, index_opts.index_dir);
+    info!("pipeline start: roots={:?}, index_dir={}, one_file_system={}", opts.roots, index_opts.index_dir, opts.one_file_system);
     let _ = pipeline_state::mark_started(&index_opts.index_dir);
 
-    for root in roots {
-        info!("pipeline scanning root: {}", root);
-        for entry in WalkDir::new(&root).follow_links(false) {
-            let entry = match entry { Ok(e) => e, Err(_) => continue };
-            if !entry.file_type().is_file() { continue; }
-            let path = entry.path();
-            let path_str = match path.to_str() { Some(s) => s.to_string(), None => continue };
-            if exclude_patterns.iter().any(|p| path_str.contains(p)) { continue; }
-            let md = match path.metadata() { Ok(m) => m, Err(_) => continue };
-            if let Some(mb) = max_bytes_opt { if md.len() > mb { continue; } }
-            let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string();
-            let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            // 使用文件实际的 UNIX 时间戳（秒）
-            let modified_ts = md
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0);
-
-            scanned_files += 1;
-            emit("scan_progress", json!({"current": scanned_files, "path": path_str, "name": file_name}));
-
-            // index_clone was unused; remove to avoid warning
-            let index_opts_clone = index_opts.clone();
-            let f_path_c = f_path.clone();
-            let f_name_c = f_name.clone();
-            let f_ext_c = f_ext.clone();
-            let f_content_c = f_content.clone();
-            let f_summary_c = f_summary.clone();
-            let f_size_c = f_size.clone();
-            let f_modified_c = f_modified.clone();
-            let app_c = app.clone();
-            let total_c = total_counter.clone();
-
-            let writer_c = writer.clone();
-            pool.spawn(move || {
-                // 统一使用删除后写入的策略，确保去重（即便查询快照未包含最新提交）
-                let term = Term::from_field_text(f_path_c, &path_str);
-
-                let mut doc = doc!(
-                    f_path_c => path_str.clone(),
-                    f_name_c => file_name.clone(),
-                    f_ext_c => ext.clone(),
-                    f_size_c => md.len(),
-                    f_modified_c => modified_ts,
-                );
-                if index_opts_clone.enable_content_parse {
-                    let text_like = ["txt", "md", "csv", "log", "json", "xml", "ini", "conf", "yaml", "yml"];
-                    if text_like.iter().any(|e| e.eq_ignore_ascii_case(ext.as_str())) {
-                        let max_bytes: usize = 1_000_000;
-                        if let Ok(mut file) = fs::File::open(&path_str) {
-                            let mut buf = Vec::with_capacity(max_bytes);
-                            let mut handle = file.by_ref().take(max_bytes as u64);
-                            if handle.read_to_end(&mut buf).is_ok() {
-                                if let Ok(text) = String::from_utf8(buf) {
-                                    doc.add_text(f_content_c, &text);
-                                    let summary: String = text.chars().take(300).collect();
-                                    if !summary.is_empty() { doc.add_text(f_summary_c, &summary); }
-                                }
+    // 投递单个条目到线程池构建索引
+    let submit = |fm: scanner::FileMeta| {
+        let index_opts_clone = index_opts.clone();
+        let app_c = app.clone();
+        let total_c = total_counter.clone();
+        let writer_c = writer.clone();
+        let done = done_tx.clone();
+        let prior = prior.clone();
+        pool.spawn(move || {
+            // 局部变量逆序释放：writer 引用先于完成信号释放，接收端返回时 writer 已无其他持有者
+            let _done = done;
+            let writer_c = writer_c;
+            // 统一使用删除后写入的策略，确保去重（即便查询快照未包含最新提交）
+            let term = fields.key_term(&fm.path, fm.raw_path.as_deref());
+
+            let mut doc = indexer::make_doc(&fields, &fm, indexer::first_indexed_ts(&prior, &fields, &fm).unwrap_or_else(|| chrono::Local::now().timestamp()));
+            if index_opts_clone.enable_content_parse && fm.kind == "file" {
+                let text_like = ["txt", "md", "csv", "log", "json", "xml", "ini", "conf", "yaml", "yml"];
+                if text_like.iter().any(|e| e.eq_ignore_ascii_case(fm.ext.as_str())) {
+                    let max_bytes: usize = 1_000_000;
+                    if let Ok(mut file) = fs::File::open(pathenc::resolve(&fm.path, fm.raw_path.as_deref())) {
+                        let mut buf = Vec::with_capacity(max_bytes);
+                        let mut handle = file.by_ref().take(max_bytes as u64);
+                        if handle.read_to_end(&mut buf).is_ok() {
+                            if let Ok(text) = String::from_utf8(buf) {
+                                indexer::add_content(&fields, &mut doc, &text);
                             }
                         }
                     }
                 }
-                if let Ok(mut w) = writer_c.lock() {
-                    let _ = w.delete_term(term);
-                    let _ = w.add_document(doc);
-                    let _ = w.commit();
-                }
-                let cur = total_c.fetch_add(1, Ordering::SeqCst) + 1;
-                let _ = app_c.emit_all("index_progress", json!({"current": cur, "name": file_name, "path": path_str}));
-            });
+            }
+            if let Ok(mut w) = writer_c.lock() {
+                let _ = w.delete_term(term);
+                let _ = w.add_document(doc);
+                let _ = w.commit();
+            }
+            let cur = total_c.fetch_add(1, Ordering::SeqCst) + 1;
+            let _ = app_c.emit_all("index_progress", json!({"current": cur, "name": fm.file_name, "path": fm.path}));
+        });
+    };
+
+    // 扫描统一由 scanner::Scanner 完成；目录在其子项遍历完成后（聚合子项数量与大小）才产出
+    let mut scan = scanner::Scanner::new(opts).label(&index_opts.index_dir).into_iter();
+    let mut last_alert_check = std::time::Instant::now();
+    for meta in scan.by_ref() {
+        scanned_files += 1;
+        emit("scan_progress", json!({"current": scanned_files, "path": meta.path, "name": meta.file_name}));
+        submit(meta);
+        if last_alert_check.elapsed() >= ALERT_CHECK_INTERVAL {
+            last_alert_check = std::time::Instant::now();
+            let (app_c, dir) = (app.clone(), index_opts.index_dir.clone());
+            tauri::async_runtime::spawn(async move { search_alerts::check(&app_c, &dir).await });
         }
     }
-
-    emit("scan_done", json!({"total": scanned_files}));
-    let _ = pipeline_state::mark_completed(&index_opts.index_dir);
+    let stats = scan.stats();
+    if stats.respect_ignore_files {
+        info!("pipeline ignore rules excluded: {:?}", stats.ignored);
+    }
+    emit("scan_done", json!({"total": scanned_files, "cancelled": stats.cancelled}));
+    // 被取消的扫描不标记完成，下次启动时会重新执行；按新 schema 重建时待替换完成后再标记
+    let rebuilding = indexer::is_rebuilding(&index_opts.index_dir);
+    if !stats.cancelled && !rebuilding {
+        let _ = pipeline_state::mark_completed(&index_opts.index_dir);
+    }
     emit("index_done", json!({"ok": true}));
+    // 线程池中的写入在后台继续进行，全部结束后释放 writer，替换重建的索引并检查关注搜索
+    drop(done_tx);
+    let (app_c, dir, cancelled) = (app.clone(), index_opts.index_dir.clone(), stats.cancelled);
+    std::thread::spawn(move || {
+        // 写入全部结束前仍视为运行中，避免新的管道在旧 writer 释放前启动
+        let _running = running;
+        while done_rx.recv().is_ok() {}
+        if let Ok(w) = Arc::try_unwrap(writer) {
+            if let Ok(w) = w.into_inner() { let _ = w.wait_merging_threads(); }
+        }
+        // 写入期间因 writer 被占用而推迟的删除
+        let written = if rebuilding { indexer::rebuild_dir(&dir) } else { dir.clone() };
+        match indexer::apply_pending_deletes(&written) {
+            Ok(n) if n > 0 => info!("pipeline: applied {} deferred deletions", n),
+            Ok(_) => {}
+            Err(e) => log::warn!("pipeline: deferred deletions failed for {}: {}", written, e),
+        }
+        if rebuilding && !cancelled {
+            match indexer::promote_rebuild(&dir) {
+                Ok(()) => { let _ = pipeline_state::mark_completed(&dir); }
+                Err(e) => log::warn!("promote rebuilt index failed for {}: {}", dir, e),
+            }
+        }
+        // 用户词变更时因 writer 被占用而推迟的重写
+        match tauri::async_runtime::block_on(dictionary::retokenize_pending(&dir)) {
+            Ok(n) if n > 0 => info!("pipeline: retokenized {} documents for pending user words", n),
+            Ok(_) => {}
+            Err(e) => log::warn!("pipeline: pending retokenize failed for {}: {}", dir, e),
+        }
+        tauri::async_runtime::block_on(search_alerts::check(&app_c, &dir));
+    });
     info!("pipeline done: scanned={}", scanned_files);
     Ok(())
 }
 
-// 前端手动触发自动扫描（立即执行一次）
+// 手动构建写入了按新版本重建中的索引后调用：重建只在完整扫描结束后替换旧索引，
+// 因此对使用该索引的集合立即执行一次完整扫描
+pub async fn finish_rebuild(index_dir: &str, app: AppHandle) {
+    if !indexer::is_rebuilding(index_dir) { return; }
+    let cfg = match config::read_config().await {
+        Ok(c) => c,
+        Err(e) => { log::warn!("finish_rebuild: read config failed: {}", e); return; }
+    };
+    let Some(c) = cfg.all_collections().into_iter().find(|c| c.index_dir == index_dir) else {
+        log::warn!("finish_rebuild: no collection uses {}; rebuilt index awaits a full scan", index_dir);
+        return;
+    };
+    info!("finish_rebuild: full scan of '{}' to complete the rebuild of {}", c.name, index_dir);
+    let _ = app.emit_all("auto_scan_start", json!({"reason": "rebuild", "collection": c.name}));
+    tauri::async_runtime::spawn(scan_and_index_pipeline_internal(
+        cfg.collection_scan_options(&c),
+        indexer::IndexOptions { index_dir: c.index_dir.clone(), enable_content_parse: false },
+        app,
+    ));
+}
+
+// 前端手动触发自动扫描（立即执行一次）；collection 为空时扫描主索引
 #[tauri::command]
-pub async fn start_auto_scan_now(window: Window) -> Result<(), String> {
+pub async fn start_auto_scan_now(window: Window, collection: Option<String>) -> Result<(), String> {
     let cfg = config::read_config().await.map_err(|e| e.to_string())?;
+    let name = collection.unwrap_or_else(|| config::DEFAULT_COLLECTION.to_string());
+    let c = cfg.collection(&name).ok_or_else(|| format!("索引集合不存在：{}", name))?;
     let handle = window.app_handle();
-    let _ = handle.emit_all("auto_scan_start", json!({"reason":"manual"}));
+    let _ = handle.emit_all("auto_scan_start", json!({"reason": "manual", "collection": c.name}));
     tauri::async_runtime::spawn(scan_and_index_pipeline_internal(
-        cfg.scan_roots.clone(),
-        cfg.exclude_patterns.clone(),
-        indexer::IndexOptions { index_dir: cfg.index_dir.clone(), enable_content_parse: false },
+        cfg.collection_scan_options(&c),
+        indexer::IndexOptions { index_dir: c.index_dir.clone(), enable_content_parse: false },
         handle,
     ));
     Ok(())
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::PathBuf;
 // 将管道状态文件写到应用配置目录，避免在开发模式下写入 src-tauri 目录导致重建
@@ -8,26 +9,49 @@ use std::path::PathBuf;
 pub struct PipelineState {
     pub last_day: Option<String>,
     pub completed: bool,
+    // 最近一次开始扫描的时间（Unix 秒），用于按小时调度
+    #[serde(default)]
+    pub last_run_ts: Option<i64>,
 }
 
-fn state_path(_index_dir: &str) -> PathBuf {
-    // 使用 Tauri 的应用配置目录作为状态文件位置
-    // 注意：这里不区分不同 index_dir，状态文件为全局单一文件
-    let base = tauri::api::path::app_config_dir(&tauri::Config::default())
-        .unwrap_or_else(|| std::path::PathBuf::from("./"));
-    base.join("scan_state.json")
+fn config_dir() -> PathBuf {
+    tauri::api::path::app_config_dir(&tauri::Config::default())
+        .unwrap_or_else(|| std::path::PathBuf::from("./"))
+}
+
+// 使用 Tauri 的应用配置目录作为状态文件位置；每个索引目录一个状态文件（按路径哈希命名），
+// 各索引集合独立调度
+fn state_path(index_dir: &str) -> PathBuf {
+    let digest = hex::encode(Sha256::digest(index_dir.as_bytes()));
+    config_dir().join(format!("scan_state_{}.json", &digest[..16]))
 }
 
 pub fn load_state(index_dir: &str) -> anyhow::Result<PipelineState> {
-    let p = state_path(index_dir);
+    let mut p = state_path(index_dir);
+    // 早期版本只有一个全局状态文件，未迁移前沿用其状态，避免升级后立即全量重扫
+    if !p.exists() { p = config_dir().join("scan_state.json"); }
     if !p.exists() { return Ok(PipelineState::default()); }
     let s = fs::read_to_string(p)?;
     Ok(serde_json::from_str(&s)?)
 }
 
-pub fn mark_started(index_dir: &str) -> anyhow::Result<()> {
+// 按调度频率判断是否应启动自动扫描："daily" 每天一次，"hourly" 距上次开始满一小时，"manual" 从不；
+// 未完成（被中断或索引已重建）的扫描总是需要重新执行
+pub fn is_due(index_dir: &str, schedule: &str) -> bool {
+    if schedule.eq_ignore_ascii_case("manual") { return false; }
+    let st = load_state(index_dir).unwrap_or_default();
+    if !st.completed { return true; }
+    if schedule.eq_ignore_ascii_case("hourly") {
+        let now = chrono::Local::now().timestamp();
+        return st.last_run_ts.map(|t| now - t >= 3600).unwrap_or(true);
+    }
     let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-    let st = PipelineState { last_day: Some(today), completed: false };
+    st.last_day.as_deref() != Some(&today)
+}
+
+pub fn mark_started(index_dir: &str) -> anyhow::Result<()> {
+    let now = chrono::Local::now();
+    let st = PipelineState { last_day: Some(now.format("%Y-%m-%d").to_string()), completed: false, last_run_ts: Some(now.timestamp()) };
     let p = state_path(index_dir);
     if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
     fs::write(p, serde_json::to_string_pretty(&st)?)?;
@@ -36,9 +60,19 @@ pub fn mark_started(index_dir: &str) -> anyhow::Result<()> {
 
 pub fn mark_completed(index_dir: &str) -> anyhow::Result<()> {
     let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-    let st = PipelineState { last_day: Some(today), completed: true };
+    let last_run_ts = load_state(index_dir).ok().and_then(|st| st.last_run_ts);
+    let st = PipelineState { last_day: Some(today), completed: true, last_run_ts };
     let p = state_path(index_dir);
     if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
     fs::write(p, serde_json::to_string_pretty(&st)?)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+// 索引被重建（如 schema 升级）后调用：清除完成标记，使自动扫描尽快重新填充
+pub fn mark_stale(index_dir: &str) -> anyhow::Result<()> {
+    let st = PipelineState { last_day: None, completed: false, last_run_ts: None };
+    let p = state_path(index_dir);
+    if let Some(dir) = p.parent() { let _ = fs::create_dir_all(dir); }
+    fs::write(p, serde_json::to_string_pretty(&st)?)?;
+    Ok(())
+}
@@ -1,7 +1,16 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use walkdir::WalkDir;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use walkdir::{DirEntry, WalkDir};
 use log::{info, debug};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::ignore_rules::{IgnoreFilter, IgnoreStats};
+use crate::{mounts, pathenc};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMeta {
@@ -10,54 +19,420 @@ pub struct FileMeta {
     pub ext: String,
     pub size: u64,
     pub modified_ts: i64,
+    // 条目类型："file" | "dir" | "symlink"
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    // 目录的直接子项数量（仅 dir）；目录的 size 为其下已收录文件的总大小
+    #[serde(default)]
+    pub child_count: Option<u64>,
+    // 路径不是合法 UTF-8 时保存原始字节的十六进制编码（见 pathenc），path 与 file_name 为有损展示值
+    #[serde(default)]
+    pub raw_path: Option<String>,
 }
 
-pub async fn scan(opts: super::ScanOptions) -> Result<Vec<FileMeta>> {
-    info!("scanner::scan start: roots={:?}", opts.roots);
-    let mut results = Vec::new();
-    let max_bytes = opts.max_file_size_mb.map(|m| m * 1024 * 1024);
-    let mut scanned_files: usize = 0;
-    let sample_every: usize = std::env::var("SE_SCAN_LOG_SAMPLE_EVERY").ok().and_then(|v| v.parse().ok()).unwrap_or(200);
-
-    for root in opts.roots {
-        info!("scanner scanning root: {}", root);
-        for entry in WalkDir::new(&root).follow_links(opts.follow_symlinks) {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
-            if !entry.file_type().is_file() {
-                continue;
+fn default_kind() -> String { "file".into() }
+
+// 条目类型判定：不跟随符号链接时，链接本身作为 symlink 收录
+fn entry_kind(ft: std::fs::FileType) -> &'static str {
+    if ft.is_dir() { "dir" } else if ft.is_symlink() { "symlink" } else { "file" }
+}
+
+// 修改时间统一取 UNIX 时间戳（秒）
+fn modified_ts(md: &std::fs::Metadata) -> i64 {
+    md.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// 目录聚合：WalkDir 先序遍历，按深度维护未完成的目录栈；
+// 目录在其子项全部遍历完后产出，此时子项数量与总大小已累计完毕
+#[derive(Default)]
+struct DirAggregator {
+    stack: Vec<(usize, FileMeta)>,
+}
+
+impl DirAggregator {
+    // 遍历到深度为 depth 的条目前调用：弹出已遍历完的目录（深度 >= depth）
+    fn close_to(&mut self, depth: usize) -> Vec<FileMeta> {
+        let mut done = Vec::new();
+        while self.stack.last().map(|(d, _)| *d >= depth).unwrap_or(false) {
+            if let Some((_, dir)) = self.stack.pop() {
+                if let Some((_, parent)) = self.stack.last_mut() { parent.size += dir.size; }
+                done.push(dir);
+            }
+        }
+        done
+    }
+
+    // 进入目录：计入父目录子项，并压栈等待子项累计
+    fn push_dir(&mut self, depth: usize, mut meta: FileMeta) {
+        self.add_child(0);
+        meta.size = 0;
+        meta.child_count = Some(0);
+        self.stack.push((depth, meta));
+    }
+
+    // 计入父目录的一个子项（文件传入其大小，其余传 0）
+    fn add_child(&mut self, size: u64) {
+        if let Some((_, parent)) = self.stack.last_mut() {
+            parent.size += size;
+            parent.child_count = Some(parent.child_count.unwrap_or(0) + 1);
+        }
+    }
+}
+
+// 最近一次扫描的统计信息（供诊断报告展示）
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ScanStats {
+    pub files: usize,
+    pub respect_ignore_files: bool,
+    pub ignored: IgnoreStats,
+    // 非 UTF-8 路径数量（以有损展示名 + 原始字节收录）
+    pub non_utf8_paths: usize,
+    // 扫描是否被取消（统计为取消前的部分结果）
+    pub cancelled: bool,
+}
+
+static LAST_SCAN_STATS: Lazy<Mutex<Option<ScanStats>>> = Lazy::new(|| Mutex::new(None));
+
+fn record_stats(stats: ScanStats) {
+    *LAST_SCAN_STATS.lock() = Some(stats);
+}
+
+pub fn last_stats() -> Option<ScanStats> {
+    LAST_SCAN_STATS.lock().clone()
+}
+
+// 排除规则匹配：当前平台的绝对路径（如 /proc、C:/Windows）按路径前缀匹配，其余按子串匹配
+pub fn is_excluded(path_str: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|p| {
+        if p.is_empty() { return false; }
+        if Path::new(p).is_absolute() { has_path_prefix(path_str, p) } else { path_str.contains(p.as_str()) }
+    })
+}
+
+fn has_path_prefix(path_str: &str, prefix: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    let (path_str, prefix) = (path_str.replace('\\', "/").to_lowercase(), prefix.replace('\\', "/").to_lowercase());
+    let prefix = prefix.trim_end_matches('/');
+    path_str == prefix || (path_str.starts_with(prefix) && path_str[prefix.len()..].starts_with('/'))
+}
+
+// 目录剪枝：命中排除规则或位于需跳过的挂载点时，不再进入该目录
+fn prune_dir(path: &Path, exclude_patterns: &[String], skip_mounts: &[PathBuf]) -> bool {
+    if skip_mounts.iter().any(|m| m.as_path() == path) {
+        debug!("skip mount point: {}", path.display());
+        return true;
+    }
+    let path_str = path.to_string_lossy();
+    if is_excluded(&path_str, exclude_patterns) {
+        debug!("excluded dir by pattern: {}", path_str);
+        return true;
+    }
+    false
+}
+
+// 正在运行的扫描：id → (标签, 取消标记)。标签为管道扫描的索引目录，或手动扫描的 MANUAL_SCAN_LABEL；
+// 扫描器（或其迭代器）释放时由 ScanHandle 移除
+type ScanRegistry = HashMap<u64, (String, Arc<AtomicBool>)>;
+static ACTIVE_SCANS: Lazy<Mutex<ScanRegistry>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_SCAN_ID: AtomicU64 = AtomicU64::new(1);
+
+// 未指定标签的扫描（scan_paths / scan_paths_progress）
+pub const MANUAL_SCAN_LABEL: &str = "manual";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveScan {
+    pub id: u64,
+    pub label: String,
+}
+
+// 扫描的登记项：随扫描器转交给迭代器，释放时注销
+struct ScanHandle {
+    id: u64,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ScanHandle {
+    fn register() -> Self {
+        let id = NEXT_SCAN_ID.fetch_add(1, Ordering::SeqCst);
+        let cancel = Arc::new(AtomicBool::new(false));
+        ACTIVE_SCANS.lock().insert(id, (MANUAL_SCAN_LABEL.to_string(), cancel.clone()));
+        ScanHandle { id, cancel }
+    }
+}
+
+impl Drop for ScanHandle {
+    fn drop(&mut self) {
+        ACTIVE_SCANS.lock().remove(&self.id);
+    }
+}
+
+pub fn active_scans() -> Vec<ActiveScan> {
+    let mut v: Vec<ActiveScan> = ACTIVE_SCANS.lock().iter().map(|(id, (label, _))| ActiveScan { id: *id, label: label.clone() }).collect();
+    v.sort_by_key(|s| s.id);
+    v
+}
+
+// 取消 id 或标签匹配的扫描，返回被通知的扫描数量
+pub fn cancel(target: &str) -> usize {
+    let id = target.parse::<u64>().ok();
+    let active = ACTIVE_SCANS.lock();
+    let mut n = 0;
+    for (scan_id, (label, flag)) in active.iter() {
+        if Some(*scan_id) == id || label == target {
+            flag.store(true, Ordering::SeqCst);
+            n += 1;
+        }
+    }
+    n
+}
+
+// 停止全部正在运行的扫描（包括其他集合的调度扫描），返回被通知的扫描数量
+pub fn cancel_all() -> usize {
+    let active = ACTIVE_SCANS.lock();
+    for (_, flag) in active.values() {
+        flag.store(true, Ordering::SeqCst);
+    }
+    active.len()
+}
+
+type EntryFilter = Box<dyn Fn(&FileMeta) -> bool + Send>;
+type ProgressFn = Box<dyn FnMut(&FileMeta, usize) + Send>;
+
+// 统一的扫描器：scan_paths、scan_paths_progress 与 pipeline 共用，
+// 同一文件无论从哪个入口扫描都产出相同的 FileMeta
+//
+// 用法：Scanner::new(opts).filter(..).on_progress(..).into_iter()，按需逐条产出；
+// 取消通过 cancel / cancel_all（cancel_scan 命令）通知，扫描在下一个条目处停止
+pub struct Scanner {
+    opts: super::ScanOptions,
+    filters: Vec<EntryFilter>,
+    progress: Option<ProgressFn>,
+    handle: ScanHandle,
+}
+
+impl Scanner {
+    // 内置过滤器：文件大小上限与文件级排除规则（目录级排除在遍历时直接剪枝）
+    pub fn new(opts: super::ScanOptions) -> Self {
+        let max_bytes = opts.max_file_size_mb.map(|m| m * 1024 * 1024);
+        let patterns = opts.exclude_patterns.clone();
+        Scanner { opts, filters: Vec::new(), progress: None, handle: ScanHandle::register() }
+            .filter(move |m| {
+                if m.kind == "dir" || !is_excluded(&m.path, &patterns) { return true; }
+                debug!("excluded by pattern: {}", m.path);
+                false
+            })
+            .filter(move |m| match max_bytes {
+                Some(mb) if m.kind == "file" && m.size > mb => { debug!("skip by size (>{} bytes): {}", mb, m.path); false }
+                _ => true,
+            })
+    }
+
+    // 扫描标签（管道扫描为索引目录），供按集合取消
+    pub fn label(self, label: &str) -> Self {
+        if let Some(entry) = ACTIVE_SCANS.lock().get_mut(&self.handle.id) { entry.0 = label.to_string(); }
+        self
+    }
+
+    // 追加过滤器：返回 false 的条目不会产出；被过滤的文件也不计入所在目录的子项数量与总大小
+    pub fn filter(mut self, f: impl Fn(&FileMeta) -> bool + Send + 'static) -> Self {
+        self.filters.push(Box::new(f));
+        self
+    }
+
+    // 进度回调：每产出一条调用一次，参数为条目与已产出数量
+    pub fn on_progress(mut self, f: impl FnMut(&FileMeta, usize) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+}
+
+impl IntoIterator for Scanner {
+    type Item = FileMeta;
+    type IntoIter = ScanIter;
+
+    fn into_iter(self) -> ScanIter {
+        info!("scanner start: roots={:?}", self.opts.roots);
+        let ignore_filter = if self.opts.respect_ignore_files { Some(IgnoreFilter::new()) } else { None };
+        let skip_mounts = mounts::skipped_mount_points(&self.opts.skip_mount_kinds);
+        // 旧配置的根目录可能只有 "/"：本地磁盘的子挂载点同样需要扫描
+        let roots: VecDeque<String> = mounts::expand_roots(&self.opts.roots, &mounts::local_disk_mounts(), self.opts.one_file_system).into();
+        if roots.len() != self.opts.roots.len() { info!("scanner effective roots: {:?}", roots); }
+        let sample_every: usize = std::env::var("SE_SCAN_LOG_SAMPLE_EVERY").ok().and_then(|v| v.parse().ok()).unwrap_or(200);
+        ScanIter {
+            roots,
+            opts: self.opts,
+            filters: self.filters,
+            progress: self.progress,
+            handle: self.handle,
+            walker: None,
+            dirs: DirAggregator::default(),
+            pending: VecDeque::new(),
+            ignore_filter,
+            skip_mounts,
+            emitted: 0,
+            non_utf8_paths: 0,
+            sample_every: sample_every.max(1),
+            finished: false,
+        }
+    }
+}
+
+pub struct ScanIter {
+    opts: super::ScanOptions,
+    roots: VecDeque<String>,
+    filters: Vec<EntryFilter>,
+    progress: Option<ProgressFn>,
+    handle: ScanHandle,
+    walker: Option<walkdir::IntoIter>,
+    dirs: DirAggregator,
+    // 已生成、待产出的条目（目录在其子项遍历完后才进入此队列）
+    pending: VecDeque<FileMeta>,
+    ignore_filter: Option<IgnoreFilter>,
+    skip_mounts: Vec<PathBuf>,
+    emitted: usize,
+    non_utf8_paths: usize,
+    sample_every: usize,
+    finished: bool,
+}
+
+impl ScanIter {
+    pub fn is_cancelled(&self) -> bool {
+        self.handle.cancel.load(Ordering::SeqCst)
+    }
+
+    pub fn stats(&self) -> ScanStats {
+        ScanStats {
+            files: self.emitted,
+            respect_ignore_files: self.ignore_filter.is_some(),
+            ignored: self.ignore_filter.as_ref().map(|f| f.stats.clone()).unwrap_or_default(),
+            non_utf8_paths: self.non_utf8_paths,
+            cancelled: self.is_cancelled(),
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.finished { return; }
+        self.finished = true;
+        let stats = self.stats();
+        info!("scanner done: total={}, cancelled={}", stats.files, stats.cancelled);
+        record_stats(stats);
+    }
+
+    // 处理单个遍历条目；返回 true 表示需跳过该目录的子项
+    fn visit(&mut self, entry: DirEntry) -> bool {
+        let is_dir = entry.file_type().is_dir();
+        if let Some(f) = self.ignore_filter.as_mut() {
+            if f.is_ignored(&entry) { return is_dir; }
+        }
+        if is_dir && entry.depth() > 0 && prune_dir(entry.path(), &self.opts.exclude_patterns, &self.skip_mounts) {
+            return true;
+        }
+        self.close_dirs(entry.depth());
+        // 扫描根目录本身不收录
+        if entry.depth() == 0 { return false; }
+        let kind = entry_kind(entry.file_type());
+        let path = entry.path();
+        // 非 UTF-8 路径不跳过：展示用有损字符串，同时保留原始字节
+        let raw_path = pathenc::raw_hex(path);
+        if raw_path.is_some() { self.non_utf8_paths += 1; }
+        let path_str = path.to_string_lossy().to_string();
+        let md = match entry.metadata() { Ok(m) => m, Err(_) => return false };
+        let file_name = path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let ext = if kind == "file" { path.extension().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default() } else { String::new() };
+
+        let meta = FileMeta {
+            path: path_str,
+            file_name,
+            ext,
+            size: if kind == "file" { md.len() } else { 0 },
+            modified_ts: modified_ts(&md),
+            kind: kind.to_string(),
+            child_count: None,
+            raw_path,
+        };
+        if is_dir {
+            self.dirs.push_dir(entry.depth(), meta);
+        } else if self.accept(&meta) {
+            self.dirs.add_child(meta.size);
+            self.pending.push_back(meta);
+        }
+        false
+    }
+
+    fn accept(&self, meta: &FileMeta) -> bool {
+        self.filters.iter().all(|f| f(meta))
+    }
+
+    // 产出深度 >= depth 的已遍历完目录（目录的过滤在聚合完成后进行）
+    fn close_dirs(&mut self, depth: usize) {
+        for dir in self.dirs.close_to(depth) {
+            if self.accept(&dir) { self.pending.push_back(dir); }
+        }
+    }
+}
+
+impl Iterator for ScanIter {
+    type Item = FileMeta;
+
+    fn next(&mut self) -> Option<FileMeta> {
+        loop {
+            if let Some(meta) = self.pending.pop_front() {
+                self.emitted += 1;
+                if self.emitted % self.sample_every == 0 {
+                    info!("scanner sample[{}]: {}", self.emitted, meta.path);
+                }
+                if let Some(cb) = self.progress.as_mut() { cb(&meta, self.emitted); }
+                return Some(meta);
             }
-            let path = entry.path();
-            let path_str = match path.to_str() { Some(s) => s.to_string(), None => continue };
-
-            // exclude patterns (simple contains for now)
-            if opts.exclude_patterns.iter().any(|p| path_str.contains(p)) { debug!("excluded by pattern: {}", path_str); continue; }
-
-            let md = match path.metadata() { Ok(m) => m, Err(_) => continue };
-            if let Some(mb) = max_bytes { if md.len() > mb { debug!("skip by size (>{} bytes): {}", mb, path_str); continue; } }
-            let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string();
-            let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            let modified_ts = md.modified().ok()
-                .and_then(|t| t.elapsed().ok())
-                .map(|e| chrono::Utc::now().timestamp() - e.as_secs() as i64)
-                .unwrap_or(0);
-
-            results.push(FileMeta {
-                path: path_str,
-                file_name,
-                ext,
-                size: md.len(),
-                modified_ts,
-            });
-
-            scanned_files += 1;
-            if scanned_files % sample_every == 0 {
-                info!("scanner sample[{}]", scanned_files);
+            if self.finished { return None; }
+            if self.is_cancelled() {
+                info!("scanner cancelled");
+                self.finish();
+                return None;
+            }
+            let next = match self.walker.as_mut() {
+                Some(w) => w.next(),
+                None => match self.roots.pop_front() {
+                    Some(root) => {
+                        info!("scanner scanning root: {}", root);
+                        self.walker = Some(
+                            WalkDir::new(&root)
+                                .follow_links(self.opts.follow_symlinks)
+                                .same_file_system(self.opts.one_file_system)
+                                .into_iter(),
+                        );
+                        continue;
+                    }
+                    None => {
+                        self.finish();
+                        continue;
+                    }
+                },
+            };
+            match next {
+                Some(Ok(entry)) => {
+                    if self.visit(entry) {
+                        if let Some(w) = self.walker.as_mut() { w.skip_current_dir(); }
+                    }
+                }
+                Some(Err(_)) => continue,
+                None => {
+                    // 当前根目录遍历结束：产出剩余目录
+                    self.close_dirs(0);
+                    self.walker = None;
+                }
             }
         }
     }
+}
+
+pub async fn scan(opts: super::ScanOptions) -> Result<Vec<FileMeta>> {
+    info!("scanner::scan start: roots={:?}", opts.roots);
+    let results: Vec<FileMeta> = Scanner::new(opts).into_iter().collect();
     info!("scanner::scan done: total_files={}", results.len());
     Ok(results)
-}
\ No newline at end of file
+}
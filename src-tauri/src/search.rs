@@ -1,13 +1,36 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use tantivy::{schema::*, Index, query::QueryParser, collector::TopDocs};
-use log::{info, debug};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ops::Bound;
+use tantivy::{schema::*, Index, DocAddress, DocId, Score, Searcher, SegmentReader, SnippetGenerator, Term, collector::{Count, DocSetCollector, TopDocs}};
+use tantivy::columnar::StrColumn;
+use tantivy::query::{AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, FuzzyTermQuery, Occur, PhraseQuery, Query, RangeQuery, RegexQuery, TermQuery, TermSetQuery};
+use log::{info, warn, debug};
+
+use crate::config::IndexCollection;
+use crate::dictionary;
+use crate::explain::{self, AnalyzedTerm, ScoreExplanation};
+use crate::facets::{FacetCollector, Facets};
+use crate::fuzzy::{self, FuzzyOptions};
+use crate::indexer::{self, IndexFields};
+use crate::open_history::{self, OpenRecord};
+use crate::tokenizer;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchFilters {
     pub ext: Option<Vec<String>>, // 文件扩展名过滤
     pub min_size: Option<u64>,
     pub max_size: Option<u64>,
+    // 条目类型过滤："file" 仅文件 | "dir" 仅文件夹；为空则不限
+    #[serde(default)]
+    pub kind: Option<String>,
+    // 修改时间区间 [起, 止]（Unix 秒，含两端），任一端为 null 表示不限
+    #[serde(default)]
+    pub time_range: Option<(Option<i64>, Option<i64>)>,
+    // 仅检索该目录（含子目录）下的条目
+    #[serde(default)]
+    pub path_prefix: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +42,32 @@ pub struct SearchResult {
     pub size: Option<u64>,
     pub modified_ts: Option<i64>,
     pub summary: Option<String>,
+    // 条目类型："file" | "dir" | "symlink"
+    pub kind: String,
+    // 文件夹的直接子项数量
+    pub child_count: Option<u64>,
+    // 非 UTF-8 路径的原始字节（十六进制），打开/删除时需原样传回
+    pub raw_path: Option<String>,
+    // 正文中命中词附近的片段；无正文命中时为空（前端退回展示 summary）
+    pub snippet: Option<String>,
+    // 高亮区间 [start, end)，以 UTF-16 码元计，可直接用于 JS 字符串切片
+    pub snippet_highlights: Vec<(usize, usize)>,
+    pub name_highlights: Vec<(usize, usize)>,
+    // 以下仅在 explain 模式下返回：名称与正文的 BM25 得分之和、命中字段与各来源的得分明细
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bm25: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_fields: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+    // 多集合检索时结果所属的集合名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    // 打开历史：通过本应用打开的次数与最近一次打开时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_opened_ts: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,69 +75,1286 @@ pub struct QueryRequest {
     pub query: String,
     pub filters: Option<SearchFilters>,
     pub index_dir: String,
+    // 分页：跳过前 offset 条，返回至多 limit 条（兼容接口文档中的 top_k）
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default, alias = "top_k")]
+    pub limit: Option<usize>,
+    // 排序字段："score"（默认）| "modified" | "size" | "name" | "path"；相同时按相关度排序
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    // 排序方向："asc" | "desc"；缺省时时间与大小为降序，名称与路径为升序
+    #[serde(default)]
+    pub sort_order: Option<String>,
+    // 文件名容错检索：fuzzy 为 true 时启用；未启用且精确检索无结果时自动以默认参数重试
+    #[serde(default)]
+    pub fuzzy: bool,
+    // 允许的编辑距离（1~2，默认 1）与必须一致的前缀字符数（默认 0）
+    #[serde(default)]
+    pub fuzzy_distance: Option<u8>,
+    #[serde(default)]
+    pub fuzzy_prefix_len: Option<usize>,
+    // 正则模式：query 作为正则表达式匹配文件名（regex_target 为 "path" 时匹配完整路径），默认不区分大小写
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub regex_target: Option<String>,
+    // 正则匹配的时间预算（毫秒），超时返回已匹配的部分结果
+    #[serde(default)]
+    pub regex_timeout_ms: Option<u64>,
+    // 排序解释：为每条结果返回命中字段与得分明细，并返回分词后的查询词（用于调试排序，开销较大）
+    #[serde(default)]
+    pub explain: bool,
+    // 分面统计：在全部命中上按扩展名、大小区间、修改年月与目录计数
+    #[serde(default)]
+    pub facets: bool,
+    // 多集合检索：按名称选择配置中的索引集合（"default" 为主索引），非空时忽略 index_dir
+    #[serde(default)]
+    pub collections: Vec<String>,
+    // 最近打开模式：仅检索打开历史中的条目，按最近打开时间排序（query 与过滤条件照常生效）
+    #[serde(default)]
+    pub recent: bool,
+}
+
+// 需求中的检索响应目标；超出时记录慢查询日志
+const SLOW_QUERY_MS: u64 = 200;
+
+// 默认每页条数与单页上限
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 1000;
+
+// 检索响应：total 为命中总数（与分页无关），elapsed_ms 为后端检索耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub total: usize,
+    pub elapsed_ms: u64,
+    pub offset: usize,
+    pub limit: usize,
+    // 本次结果是否来自容错检索（显式启用或精确检索无结果时自动启用）
+    pub fuzzy_applied: bool,
+    // 正则模式下超出时间预算被提前终止（total 仅为已扫描部分的命中数）
+    pub timed_out: bool,
+    pub results: Vec<SearchResult>,
+    // explain 模式下的查询词（字段 + 分词结果）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyzed_terms: Option<Vec<AnalyzedTerm>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Facets>,
+    // 无结果时按词典给出的拼写纠正后的查询
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did_you_mean: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortField {
+    Score,
+    Modified,
+    Size,
+    Name,
+    Path,
+}
+
+fn parse_sort(by: Option<&str>, order: Option<&str>) -> (SortField, bool) {
+    let field = match by.map(|s| s.to_ascii_lowercase()).as_deref() {
+        Some("modified") | Some("modified_ts") | Some("time") => SortField::Modified,
+        Some("size") => SortField::Size,
+        Some("name") => SortField::Name,
+        Some("path") => SortField::Path,
+        _ => SortField::Score,
+    };
+    let desc = match order.map(|s| s.to_ascii_lowercase()).as_deref() {
+        Some("asc") => false,
+        Some("desc") => true,
+        _ => !matches!(field, SortField::Name | SortField::Path),
+    };
+    (field, desc)
+}
+
+// 排序键：TopDocs 保留键最大的文档，升序时对键取反；
+// 名称与路径的词典序号仅在段内有效，因此取出字符串本身比较
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum SortKey {
+    Num(u64),
+    Text(String),
+    TextAsc(Reverse<String>),
+}
+
+// 按排序字段检索：(排序键, 相关度) 作为复合得分，相关度作为次级排序
+fn sorted_search(
+    searcher: &Searcher,
+    query: &dyn Query,
+    field: SortField,
+    desc: bool,
+    limit: usize,
+    offset: usize,
+) -> Result<(Vec<(Score, DocAddress)>, usize)> {
+    let collector = TopDocs::with_limit(limit).and_offset(offset).tweak_score(move |seg: &SegmentReader| {
+        let ff = seg.fast_fields();
+        let size_col = if field == SortField::Size { ff.u64("size").ok() } else { None };
+        let modified_col = if field == SortField::Modified { ff.i64("modified_ts").ok() } else { None };
+        let text_col = match field {
+            SortField::Name => ff.str("name").ok().flatten(),
+            SortField::Path => ff.str("path").ok().flatten(),
+            _ => None,
+        };
+        let mut buf = String::new();
+        move |doc: DocId, score: Score| {
+            let key = if let Some(col) = &text_col {
+                buf.clear();
+                if let Some(ord) = col.term_ords(doc).next() { let _ = col.ord_to_str(ord, &mut buf); }
+                if desc { SortKey::Text(buf.clone()) } else { SortKey::TextAsc(Reverse(buf.clone())) }
+            } else {
+                // i64 映射为保序的 u64；缺失值在两种方向下都排在最后
+                let v = size_col.as_ref().and_then(|c| c.first(doc))
+                    .or_else(|| modified_col.as_ref().and_then(|c| c.first(doc)).map(|t| (t as u64) ^ (1 << 63)));
+                match v {
+                    Some(v) if desc => SortKey::Num(v),
+                    Some(v) => SortKey::Num(u64::MAX - v),
+                    None => SortKey::Num(0),
+                }
+            };
+            (key, score)
+        }
+    });
+    let (docs, total) = searcher.search(query, &(collector, Count))?;
+    Ok((docs.into_iter().map(|((_, score), addr)| (score, addr)).collect(), total))
+}
+
+// frecency 加权：得分乘以 1 + weight × f / (f + FRECENCY_HALF)，f 为打开历史的 frecency；
+// 短查询（不超过 SHORT_QUERY_CHARS 个字符）区分度低，常用文件的权重更高
+const FRECENCY_HALF: f32 = 100.0;
+const FRECENCY_WEIGHT: f32 = 0.5;
+const FRECENCY_WEIGHT_SHORT: f32 = 4.0;
+const SHORT_QUERY_CHARS: usize = 4;
+
+// 打开历史中的路径在段内 path 列中的序号 → 对应的值（不在本段的路径跳过）
+pub(crate) fn history_ords<T: Copy>(seg: &SegmentReader, values: &[(String, T)]) -> (Option<StrColumn>, HashMap<u64, T>) {
+    let col = seg.fast_fields().str("path").ok().flatten();
+    let mut ords = HashMap::new();
+    if let Some(col) = &col {
+        for (path, v) in values {
+            if let Ok(Some(ord)) = col.dictionary().term_ord(path.as_bytes()) { ords.insert(ord, *v); }
+        }
+    }
+    (col, ords)
+}
+
+// 相关度排序并按打开历史的 frecency 加权
+fn frecency_search(
+    searcher: &Searcher,
+    query: &dyn Query,
+    history: &HashMap<String, OpenRecord>,
+    weight: Score,
+    now: i64,
+    limit: usize,
+    offset: usize,
+) -> Result<(Vec<(Score, DocAddress)>, usize)> {
+    let boosts: Vec<(String, Score)> = history.iter().map(|(path, rec)| (path.clone(), frecency_boost(rec, now, weight))).collect();
+    let collector = TopDocs::with_limit(limit).and_offset(offset).tweak_score(move |seg: &SegmentReader| {
+        let (col, boosts) = history_ords(seg, &boosts);
+        move |doc: DocId, score: Score| {
+            let boost = col.as_ref().and_then(|c| c.term_ords(doc).next()).and_then(|ord| boosts.get(&ord)).copied().unwrap_or(1.0);
+            score * boost
+        }
+    });
+    Ok(searcher.search(query, &(collector, Count))?)
+}
+
+// 打开历史对得分的乘数
+fn frecency_boost(rec: &OpenRecord, now: i64, weight: Score) -> Score {
+    let f = open_history::frecency(rec, now);
+    1.0 + weight * f / (f + FRECENCY_HALF)
+}
+
+// 最近打开模式：按最近一次打开时间降序，相同时按相关度
+fn recent_search(
+    searcher: &Searcher,
+    query: &dyn Query,
+    history: &HashMap<String, OpenRecord>,
+    limit: usize,
+    offset: usize,
+) -> Result<(Vec<(Score, DocAddress)>, usize)> {
+    let opened: Vec<(String, i64)> = history.iter().map(|(path, rec)| (path.clone(), rec.last_ts)).collect();
+    let collector = TopDocs::with_limit(limit).and_offset(offset).tweak_score(move |seg: &SegmentReader| {
+        let (col, opened) = history_ords(seg, &opened);
+        move |doc: DocId, score: Score| {
+            let ts = col.as_ref().and_then(|c| c.term_ords(doc).next()).and_then(|ord| opened.get(&ord)).copied().unwrap_or(i64::MIN);
+            (ts, score)
+        }
+    });
+    let (docs, total) = searcher.search(query, &(collector, Count))?;
+    Ok((docs.into_iter().map(|((_, score), addr)| (score, addr)).collect(), total))
+}
+
+// 片段最大字符数
+const SNIPPET_MAX_CHARS: usize = 160;
+
+// 字节区间转换为 UTF-16 码元区间
+fn utf16_ranges(text: &str, ranges: &[std::ops::Range<usize>]) -> Vec<(usize, usize)> {
+    let to_utf16 = |byte: usize| text[..byte].encode_utf16().count();
+    ranges.iter().map(|r| (to_utf16(r.start), to_utf16(r.end))).collect()
+}
+
+// 过滤条件转换为子查询：与文本查询以 Must 组合，得分固定为 0，不影响相关度排序
+fn filter_clauses(f: &IndexFields, filters: &SearchFilters) -> Vec<(Occur, Box<dyn Query>)> {
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    let mut push = |q: Box<dyn Query>| clauses.push((Occur::Must, Box::new(ConstScoreQuery::new(q, 0.0)) as Box<dyn Query>));
+    if let Some(exts) = &filters.ext {
+        // 扩展名在扫描时已转为小写，这里同样归一化并去掉前导 "."
+        let terms: Vec<Term> = exts
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .map(|e| Term::from_field_text(f.ext, &e))
+            .collect();
+        if !terms.is_empty() { push(Box::new(TermSetQuery::new(terms))); }
+    }
+    if filters.min_size.is_some() || filters.max_size.is_some() {
+        let lower = filters.min_size.map(Bound::Included).unwrap_or(Bound::Unbounded);
+        let upper = filters.max_size.map(Bound::Included).unwrap_or(Bound::Unbounded);
+        push(Box::new(RangeQuery::new_u64_bounds("size".to_string(), lower, upper)));
+    }
+    if let Some(k) = filters.kind.as_deref().filter(|k| !k.is_empty()) {
+        let term = Term::from_field_text(f.kind, &k.to_lowercase());
+        push(Box::new(TermQuery::new(term, IndexRecordOption::Basic)));
+    }
+    if let Some((from, to)) = filters.time_range.filter(|r| r.0.is_some() || r.1.is_some()) {
+        let lower = from.map(Bound::Included).unwrap_or(Bound::Unbounded);
+        let upper = to.map(Bound::Included).unwrap_or(Bound::Unbounded);
+        push(Box::new(RangeQuery::new_i64_bounds("modified_ts".to_string(), lower, upper)));
+    }
+    // 路径前缀按层级 token 精确匹配（与索引时同样归一化）；"/" 等归一化后为空的前缀不过滤
+    if let Some(prefix) = filters.path_prefix.as_deref().map(tokenizer::normalize_path).filter(|p| !p.is_empty()) {
+        let term = Term::from_field_text(f.path_tree, &prefix);
+        push(Box::new(TermQuery::new(term, IndexRecordOption::Basic)));
+    }
+    clauses
+}
+
+// 容错子查询的权重：低于精确匹配，使精确命中的文档排在前面
+const FUZZY_BOOST: Score = 0.5;
+
+// 拼音子查询的权重：低于名称与正文的直接命中
+const PINYIN_BOOST: Score = 0.8;
+
+// 同义词子查询的权重：原词命中排在同义词命中之前
+const SYNONYM_BOOST: Score = 0.8;
+
+// explain 中拼音、容错与同义词来源的标签（名称与正文直接以字段名标注）
+const LABEL_PINYIN: &str = "name_pinyin";
+const LABEL_FUZZY: &str = "name_fuzzy";
+const LABEL_SYNONYM: &str = "synonym";
+// 打开历史加权（乘在总分上，见 frecency_search）
+const LABEL_FRECENCY: &str = "frecency";
+
+fn label_boost(label: &str) -> Score {
+    match label {
+        LABEL_PINYIN => PINYIN_BOOST,
+        LABEL_FUZZY => FUZZY_BOOST,
+        LABEL_SYNONYM => SYNONYM_BOOST,
+        _ => 1.0,
+    }
+}
+
+fn pinyin_query(f: &IndexFields, text: &str) -> Option<Box<dyn Query>> {
+    let words: Vec<String> = text.split_whitespace().map(|w| w.to_ascii_lowercase()).collect();
+    if words.is_empty() || !words.iter().all(|w| w.chars().all(|c| c.is_ascii_alphabetic())) { return None; }
+    let prefix = |field: Field, w: &str| -> Box<dyn Query> { Box::new(FuzzyTermQuery::new_prefix(Term::from_field_text(field, w), 0, false)) };
+    // 每个词都需匹配（全拼或首字母）
+    let each: Vec<(Occur, Box<dyn Query>)> = words.iter()
+        .map(|w| (Occur::Must, Box::new(BooleanQuery::new(vec![(Occur::Should, prefix(f.name_pinyin, w)), (Occur::Should, prefix(f.name_initials, w))])) as Box<dyn Query>))
+        .collect();
+    if words.len() == 1 { return each.into_iter().next().map(|(_, q)| q); }
+    // 多个词另外整体拼接匹配：单个汉语词的全拼（"zhang san" 对应 "张三" 的 zhangsan）
+    Some(Box::new(BooleanQuery::new(vec![
+        (Occur::Should, Box::new(BooleanQuery::new(each)) as Box<dyn Query>),
+        (Occur::Should, prefix(f.name_pinyin, &words.concat())),
+    ])))
+}
+
+// ---------------- 查询语言 ----------------
+// 单个输入框内的类 Everything 语法：
+//   word                 名称或正文包含该词（多个词默认同时满足）
+//   "exact phrase"       短语（各词按顺序相邻）
+//   "budget review"~3    邻近：各词之间允许至多 3 个位置的距离
+//   name:report  content:"年度 预算"   仅在名称或正文中匹配词或短语
+//   -word / -ext:log     排除
+//   a OR b               任一满足（OR 需大写，绑定两侧相邻的条件）
+//   ext:pdf,docx         扩展名
+//   size:>10mb  size:1mb..20mb  size:<=512kb
+//   modified:2024  modified:2024-01..2024-03  modified:>=2024-05-01  modified:today
+//   path:/home/me/docs   绝对路径：位于该目录之下（按路径层级匹配，与路径前缀过滤一致）
+//   path:projects/       其他取值：完整路径包含该子串（区分大小写）
+//   kind:dir | kind:file
+
+#[derive(Debug, thiserror::Error)]
+#[error("查询语法错误（第 {pos} 个字符）：{message}")]
+pub struct QuerySyntaxError {
+    // 出错位置（从 1 开始的字符序号）
+    pub pos: usize,
+    pub message: String,
+}
+
+fn syntax_error(pos: usize, message: impl Into<String>) -> anyhow::Error {
+    QuerySyntaxError { pos: pos + 1, message: message.into() }.into()
+}
+
+// 文本条件的匹配范围
+#[derive(Debug, Clone, Copy)]
+enum TextScope {
+    Any,
+    Name,
+    Content,
+}
+
+#[derive(Debug, Clone)]
+enum Clause {
+    // phrase 为 Some(slop) 时按短语匹配，slop 为允许的位置距离（0 即严格相邻）
+    Text { text: String, phrase: Option<u32>, scope: TextScope },
+    Ext(Vec<String>),
+    Size(Bound<u64>, Bound<u64>),
+    Modified(Bound<i64>, Bound<i64>),
+    // 位于该目录之下（已归一化，按 path_tree 层级 token 匹配）
+    PathUnder(String),
+    // 完整路径包含该子串
+    Path(String),
+    Kind(String),
+}
+
+#[derive(Debug, Clone)]
+struct Item {
+    negated: bool,
+    clause: Clause,
+}
+
+// 解析结果：外层为同时满足（AND），内层为 OR 连接的备选项
+#[derive(Debug, Default)]
+struct ParsedQuery {
+    groups: Vec<Vec<Item>>,
+}
+
+impl Item {
+    // 普通检索词：未排除、不限定字段的非短语文本
+    fn is_free_text(&self) -> bool {
+        !self.negated && matches!(self.clause, Clause::Text { phrase: None, scope: TextScope::Any, .. })
+    }
+}
+
+impl ParsedQuery {
+    // 拆分为普通检索词组成的条件组与其余条件组（字段条件、短语、排除及含这些条件的 OR）；
+    // 拼音与容错等附加匹配只作为前者的备选，后者对全部备选同样生效
+    fn split(self) -> (Vec<Vec<Item>>, Vec<Vec<Item>>) {
+        self.groups.into_iter().partition(|g| g.iter().all(Item::is_free_text))
+    }
+}
+
+// 普通检索词（供拼音与容错等附加匹配使用）
+fn free_text(groups: &[Vec<Item>]) -> String {
+    let words: Vec<&str> = groups
+        .iter()
+        .flatten()
+        .filter_map(|i| match &i.clause { Clause::Text { text, .. } => Some(text.as_str()), _ => None })
+        .collect();
+    words.join(" ")
+}
+
+const FIELD_NAMES: &[&str] = &["ext", "size", "modified", "path", "kind", "name", "content"];
+
+fn parse_query_language(input: &str) -> Result<ParsedQuery> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    let mut groups: Vec<Vec<Item>> = Vec::new();
+    let mut pending_or: Option<usize> = None;
+    while i < chars.len() {
+        if chars[i].is_whitespace() { i += 1; continue; }
+        let start = i;
+        let negated = chars[i] == '-' && i + 1 < chars.len() && !chars[i + 1].is_whitespace();
+        if negated { i += 1; }
+        let word = read_word(&chars, i)?;
+        i = word.end;
+        if !negated && !word.quoted && word.text == "OR" {
+            if groups.is_empty() || pending_or.is_some() { return Err(syntax_error(start, "OR 前缺少检索条件")); }
+            pending_or = Some(start);
+            continue;
+        }
+        let clause = parse_clause(&word, start + usize::from(negated))?;
+        let item = Item { negated, clause };
+        match (pending_or.take(), groups.last_mut()) {
+            (Some(_), Some(last)) => last.push(item),
+            _ => groups.push(vec![item]),
+        }
+    }
+    if let Some(pos) = pending_or { return Err(syntax_error(pos, "OR 后缺少检索条件")); }
+    Ok(ParsedQuery { groups })
+}
+
+struct Word {
+    text: String,
+    // 含引号部分（短语）；引号后可跟 ~N 指定邻近距离
+    quoted: bool,
+    slop: Option<u32>,
+    // 首个引号之前的字节数（无引号时为全长）；"字段:" 前缀只在引号之前识别
+    bare_len: usize,
+    end: usize,
+}
+
+// 读取一个词：以空白或闭合引号（及其后的 ~N）结束；引号包围的部分（可出现在开头或 "字段:" 之后）原样保留
+fn read_word(chars: &[char], start: usize) -> Result<Word> {
+    let mut text = String::new();
+    let mut i = start;
+    let mut quoted = false;
+    let mut slop = None;
+    let mut bare_len = usize::MAX;
+    while i < chars.len() && !chars[i].is_whitespace() {
+        if chars[i] != '"' {
+            text.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let open = i;
+        bare_len = text.len();
+        i += 1;
+        while i < chars.len() && chars[i] != '"' { text.push(chars[i]); i += 1; }
+        if i >= chars.len() { return Err(syntax_error(open, "引号未闭合")); }
+        i += 1;
+        quoted = true;
+        if chars.get(i) == Some(&'~') {
+            let digits: String = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).collect();
+            slop = Some(digits.parse::<u32>().map_err(|_| syntax_error(i, "~ 后需为邻近距离（整数）"))?);
+            i += 1 + digits.len();
+        }
+        break;
+    }
+    let bare_len = bare_len.min(text.len());
+    Ok(Word { text, quoted, slop, bare_len, end: i })
+}
+
+fn parse_clause(word: &Word, pos: usize) -> Result<Clause> {
+    let phrase = word.quoted.then(|| word.slop.unwrap_or(0));
+    let (name, value) = match word.text.split_once(':') {
+        Some((n, v)) if n.len() < word.bare_len && FIELD_NAMES.contains(&n.to_ascii_lowercase().as_str()) => (n.to_ascii_lowercase(), v),
+        // 未知前缀（如 C:\ 或 http:）按普通词处理
+        _ => return Ok(Clause::Text { text: word.text.clone(), phrase, scope: TextScope::Any }),
+    };
+    let vpos = pos + name.chars().count() + 1;
+    if value.is_empty() { return Err(syntax_error(vpos, format!("{} 缺少取值", name))); }
+    match name.as_str() {
+        "name" => return Ok(Clause::Text { text: value.to_string(), phrase, scope: TextScope::Name }),
+        "content" => return Ok(Clause::Text { text: value.to_string(), phrase, scope: TextScope::Content }),
+        _ if word.slop.is_some() => return Err(syntax_error(vpos, format!("{} 不支持 ~ 邻近距离", name))),
+        _ => {}
+    }
+    match name.as_str() {
+        "ext" => {
+            let exts: Vec<String> = value
+                .split(',')
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .filter(|e| !e.is_empty())
+                .collect();
+            if exts.is_empty() { return Err(syntax_error(vpos, "ext 缺少扩展名")); }
+            Ok(Clause::Ext(exts))
+        }
+        "size" => {
+            let (lo, hi) = parse_range(value, vpos, |s| parse_size(s).map(|n| (n, n.saturating_add(1))))?;
+            Ok(Clause::Size(lo, hi))
+        }
+        "modified" => {
+            let (lo, hi) = parse_range(value, vpos, parse_date)?;
+            Ok(Clause::Modified(lo, hi))
+        }
+        "path" if is_absolute_path(value) => {
+            let dir = tokenizer::normalize_path(value);
+            if dir.is_empty() { return Err(syntax_error(vpos, "path 不能为根目录")); }
+            Ok(Clause::PathUnder(dir))
+        }
+        "path" => Ok(Clause::Path(value.to_string())),
+        _ => {
+            let k = value.to_ascii_lowercase();
+            if !["file", "dir", "symlink"].contains(&k.as_str()) {
+                return Err(syntax_error(vpos, format!("kind 仅支持 file、dir 或 symlink，而不是 {}", value)));
+            }
+            Ok(Clause::Kind(k))
+        }
+    }
+}
+
+// 绝对路径（与平台无关地识别 "/..."、"\\..." 与 "C:..."）
+fn is_absolute_path(value: &str) -> bool {
+    let b = value.as_bytes();
+    value.starts_with('/') || value.starts_with('\\') || (b.len() >= 2 && b[0].is_ascii_alphabetic() && b[1] == b':')
+}
+
+// 区间取值：">x" ">=x" "<x" "<=x" "a..b"（两端可省略）或单个值；
+// parse 返回单个值覆盖的半开区间 [start, end)，如日期 2024-03 对应整个三月
+fn parse_range<T: Copy>(value: &str, pos: usize, parse: impl Fn(&str) -> Option<(T, T)>) -> Result<(Bound<T>, Bound<T>)> {
+    let one = |s: &str, at: usize| parse(s.trim()).ok_or_else(|| syntax_error(at, format!("无法识别的取值：{}", s)));
+    if let Some(rest) = value.strip_prefix(">=") { return Ok((Bound::Included(one(rest, pos + 2)?.0), Bound::Unbounded)); }
+    if let Some(rest) = value.strip_prefix("<=") { return Ok((Bound::Unbounded, Bound::Excluded(one(rest, pos + 2)?.1))); }
+    if let Some(rest) = value.strip_prefix('>') { return Ok((Bound::Included(one(rest, pos + 1)?.1), Bound::Unbounded)); }
+    if let Some(rest) = value.strip_prefix('<') { return Ok((Bound::Unbounded, Bound::Excluded(one(rest, pos + 1)?.0))); }
+    if let Some((a, b)) = value.split_once("..") {
+        let lo = if a.is_empty() { Bound::Unbounded } else { Bound::Included(one(a, pos)?.0) };
+        let hi = if b.is_empty() { Bound::Unbounded } else { Bound::Excluded(one(b, pos + a.chars().count() + 2)?.1) };
+        return Ok((lo, hi));
+    }
+    let (lo, hi) = one(value, pos)?;
+    Ok((Bound::Included(lo), Bound::Excluded(hi)))
+}
+
+// 大小：数字 + 可选单位（b/kb/mb/gb/tb，按 1024 进制，k/m/g/t 亦可）
+fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim().to_ascii_lowercase();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: f64 = num.parse().ok()?;
+    let mult: u64 = match unit.trim() {
+        "" | "b" => 1,
+        "k" | "kb" => 1 << 10,
+        "m" | "mb" => 1 << 20,
+        "g" | "gb" => 1 << 30,
+        "t" | "tb" => 1 << 40,
+        _ => return None,
+    };
+    Some((n * mult as f64) as u64)
+}
+
+// 日期（本地时区）：YYYY、YYYY-MM、YYYY-MM-DD、today、yesterday；返回覆盖的时间段 [start, end)
+fn parse_date(s: &str) -> Option<(i64, i64)> {
+    use chrono::{Duration, Local, NaiveDate, TimeZone};
+    let ts = |d: NaiveDate| Local.from_local_datetime(&d.and_hms_opt(0, 0, 0)?).earliest().map(|t| t.timestamp());
+    let s = s.trim().to_ascii_lowercase();
+    let today = Local::now().date_naive();
+    let (start, end) = match s.as_str() {
+        "today" => (today, today + Duration::days(1)),
+        "yesterday" => (today - Duration::days(1), today),
+        _ => {
+            let parts: Vec<&str> = s.split('-').collect();
+            let nums: Vec<u32> = parts.iter().map(|p| p.parse().ok()).collect::<Option<_>>()?;
+            match nums.as_slice() {
+                [y] => (NaiveDate::from_ymd_opt(*y as i32, 1, 1)?, NaiveDate::from_ymd_opt(*y as i32 + 1, 1, 1)?),
+                [y, m] => {
+                    let start = NaiveDate::from_ymd_opt(*y as i32, *m, 1)?;
+                    let end = if *m == 12 { NaiveDate::from_ymd_opt(*y as i32 + 1, 1, 1)? } else { NaiveDate::from_ymd_opt(*y as i32, m + 1, 1)? };
+                    (start, end)
+                }
+                [y, m, d] => {
+                    let start = NaiveDate::from_ymd_opt(*y as i32, *m, *d)?;
+                    (start, start + Duration::days(1))
+                }
+                _ => return None,
+            }
+        }
+    };
+    Some((ts(start)?, ts(end)?))
+}
+
+// 文本条件：按字段分词器切分，在范围内任一字段命中。同一位置的短词与完整词只保留完整词；
+// 普通词要求各词全部出现，短语要求按位置相邻（slop 内）
+fn text_clause(index: &Index, f: &IndexFields, text: &str, phrase: Option<u32>, scope: TextScope) -> Result<Option<Box<dyn Query>>> {
+    let fields = match scope {
+        TextScope::Any => vec![f.name, f.content],
+        TextScope::Name => vec![f.name],
+        TextScope::Content => vec![f.content],
+    };
+    let mut per_field: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for field in fields {
+        let mut analyzer = index.tokenizer_for_field(field)?;
+        // (位置, 字节长度, 词)
+        let mut words: Vec<(usize, usize, String)> = Vec::new();
+        let mut stream = analyzer.token_stream(text);
+        while stream.advance() {
+            let t = stream.token();
+            let len = t.offset_to - t.offset_from;
+            match words.last_mut() {
+                Some(w) if w.0 == t.position => { if len >= w.1 { *w = (t.position, len, t.text.clone()); } }
+                _ => words.push((t.position, len, t.text.clone())),
+            }
+        }
+        let Some(first) = words.first().map(|w| w.0) else { continue };
+        let mut terms: Vec<(usize, Term)> = words.into_iter().map(|(p, _, w)| (p - first, Term::from_field_text(field, &w))).collect();
+        let q: Box<dyn Query> = match phrase {
+            _ if terms.len() == 1 => word_query(field, terms.remove(0).1),
+            Some(slop) => Box::new(PhraseQuery::new_with_offset_and_slop(terms, slop)),
+            None => Box::new(BooleanQuery::new(terms.into_iter().map(|(_, t)| (Occur::Must, word_query(field, t))).collect())),
+        };
+        per_field.push((Occur::Should, explain::labeled(index.schema().get_field_name(field), q)));
+    }
+    if per_field.is_empty() { return Ok(None); }
+    Ok(Some(Box::new(BooleanQuery::new(per_field))))
+}
+
+// 单个词的查询；用户词同时匹配其加入词典前的切分（各词都须出现，与加入前的检索结果一致），
+// 使此前写入的文档仍能命中。短语查询中的用户词不展开
+fn word_query(field: Field, term: Term) -> Box<dyn Query> {
+    let exact: Box<dyn Query> = Box::new(TermQuery::new(term.clone(), IndexRecordOption::WithFreqs));
+    let Some(split) = term.value().as_str().and_then(dictionary::split_of) else { return exact };
+    let old: Vec<(Occur, Box<dyn Query>)> = split
+        .iter()
+        .map(|w| (Occur::Must, Box::new(TermQuery::new(Term::from_field_text(field, w), IndexRecordOption::WithFreqs)) as Box<dyn Query>))
+        .collect();
+    Box::new(BooleanQuery::new(vec![(Occur::Should, exact), (Occur::Should, Box::new(BooleanQuery::new(old)))]))
+}
+
+// 普通词按同义词词典展开：原词或任一同义词命中即可（短语按原文匹配，不展开）
+fn synonym_clause(index: &Index, f: &IndexFields, text: &str, scope: TextScope) -> Result<Option<Box<dyn Query>>> {
+    let original = text_clause(index, f, text, None, scope)?;
+    let synonyms = dictionary::synonyms_of(text);
+    if synonyms.is_empty() { return Ok(original); }
+    let mut alts: Vec<(Occur, Box<dyn Query>)> = original.into_iter().map(|q| (Occur::Should, q)).collect();
+    for syn in synonyms {
+        if let Some(q) = text_clause(index, f, &syn, None, scope)? {
+            alts.push((Occur::Should, explain::labeled(LABEL_SYNONYM, Box::new(BoostQuery::new(q, SYNONYM_BOOST)))));
+        }
+    }
+    if alts.is_empty() { return Ok(None); }
+    Ok(Some(Box::new(BooleanQuery::new(alts))))
+}
+
+// 编译单个条件；过滤类条件得分固定为 0，仅文本条件参与相关度
+fn compile_clause(index: &Index, f: &IndexFields, clause: &Clause) -> Result<Option<Box<dyn Query>>> {
+    let zero = |q: Box<dyn Query>| -> Option<Box<dyn Query>> { Some(Box::new(ConstScoreQuery::new(q, 0.0))) };
+    Ok(match clause {
+        Clause::Text { text, phrase: None, scope } => synonym_clause(index, f, text, *scope)?,
+        Clause::Text { text, phrase, scope } => text_clause(index, f, text, *phrase, *scope)?,
+        Clause::Ext(exts) => zero(Box::new(TermSetQuery::new(exts.iter().map(|e| Term::from_field_text(f.ext, e))))),
+        Clause::Size(lo, hi) => zero(Box::new(RangeQuery::new_u64_bounds("size".to_string(), *lo, *hi))),
+        Clause::Modified(lo, hi) => zero(Box::new(RangeQuery::new_i64_bounds("modified_ts".to_string(), *lo, *hi))),
+        Clause::PathUnder(dir) => zero(Box::new(TermQuery::new(Term::from_field_text(f.path_tree, dir), IndexRecordOption::Basic))),
+        Clause::Path(p) => zero(Box::new(RegexQuery::from_pattern(&format!(".*{}.*", regex::escape(p)), f.path)?)),
+        Clause::Kind(k) => zero(Box::new(TermQuery::new(Term::from_field_text(f.kind, k), IndexRecordOption::Basic))),
+    })
 }
 
-pub async fn query(req: QueryRequest) -> Result<Vec<SearchResult>> {
-    info!("search::query start: q='{}', index='{}'", req.query, req.index_dir);
-    let index = Index::open_in_dir(&req.index_dir)?;
-    let reader = index.reader()?;
-    let searcher = reader.searcher();
-    let schema = index.schema();
-    let f_name = schema.get_field("name").unwrap();
-    let f_content = schema.get_field("content").unwrap();
-    let f_ext = schema.get_field("ext").unwrap();
-    let f_path = schema.get_field("path").unwrap();
-    let f_size = schema.get_field("size").ok();
-    let f_modified = schema.get_field("modified_ts").ok();
-    let f_summary = schema.get_field("summary").ok();
-    let parser = QueryParser::for_index(&index, vec![f_name, f_content]);
-    let query = parser.parse_query(&req.query)?;
-
-    let top_docs = searcher.search(&query, &TopDocs::with_limit(50))?;
+// 将条件组编译为子句：外层同时满足（AND），组内为 OR 备选；单个排除条件为 MustNot
+fn compile_groups(index: &Index, f: &IndexFields, groups: &[Vec<Item>]) -> Result<Vec<(Occur, Box<dyn Query>)>> {
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for group in groups {
+        if let [item] = group.as_slice() {
+            if let Some(q) = compile_clause(index, f, &item.clause)? {
+                clauses.push((if item.negated { Occur::MustNot } else { Occur::Must }, q));
+            }
+            continue;
+        }
+        let mut alts: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for item in group {
+            let Some(q) = compile_clause(index, f, &item.clause)? else { continue };
+            let q: Box<dyn Query> = if item.negated {
+                Box::new(BooleanQuery::new(vec![(Occur::Must, Box::new(AllQuery) as Box<dyn Query>), (Occur::MustNot, q)]))
+            } else {
+                q
+            };
+            alts.push((Occur::Should, q));
+        }
+        if !alts.is_empty() { clauses.push((Occur::Must, Box::new(BooleanQuery::new(alts)))); }
+    }
+    Ok(clauses)
+}
+
+// 组合查询：普通检索词的直接匹配与拼音、容错备选（Should）构成文本部分，
+// 字段条件、短语、排除与请求中的过滤条件作为顶层 Must/MustNot，对全部备选同样生效；
+// fuzzy 非空时追加文件名容错备选
+fn build_query(index: &Index, searcher: &Searcher, f: &IndexFields, req: &QueryRequest, fuzzy: Option<FuzzyOptions>) -> Result<Box<dyn Query>> {
+    let (text_groups, other_groups) = parse_query_language(&req.query)?.split();
+    let free_text = free_text(&text_groups);
+    // 没有普通检索词时匹配全部文档，仅按其余条件筛选
+    let text_clauses = compile_groups(index, f, &text_groups)?;
+    let mut text_query: Box<dyn Query> = if text_clauses.is_empty() { Box::new(AllQuery) } else { Box::new(BooleanQuery::new(text_clauses)) };
+    // 纯拉丁字母查询同时按拼音匹配中文名称（全拼或首字母，按前缀匹配以支持边输入边检索）
+    if let Some(pq) = pinyin_query(f, &free_text) {
+        text_query = Box::new(BooleanQuery::new(vec![
+            (Occur::Should, text_query),
+            (Occur::Should, explain::labeled(LABEL_PINYIN, Box::new(BoostQuery::new(pq, PINYIN_BOOST)))),
+        ]));
+    }
+    if let Some(opts) = fuzzy.filter(|_| !free_text.is_empty()) {
+        if let Some(fq) = fuzzy::name_query(index, searcher, f.name, &free_text, opts)? {
+            debug!("search::query fuzzy: {:?}", opts);
+            text_query = Box::new(BooleanQuery::new(vec![
+                (Occur::Should, text_query),
+                (Occur::Should, explain::labeled(LABEL_FUZZY, Box::new(BoostQuery::new(fq, FUZZY_BOOST)))),
+            ]));
+        }
+    }
+    let mut clauses = vec![(Occur::Must, text_query)];
+    clauses.extend(compile_groups(index, f, &other_groups)?);
+    let filters = req.filters.as_ref().map(|flt| filter_clauses(f, flt)).unwrap_or_default();
+    if !filters.is_empty() { debug!("search::query filters: {}", filters.len()); }
+    clauses.extend(filters);
+    if clauses.len() == 1 { return Ok(clauses.remove(0).1); }
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
+
+// 关注提醒使用：返回首次写入时间不早于 indexed_since 的匹配条目（路径, 名称），至多 cap 条；
+// 不计分也不排序，开销只与新条目数量有关。先刷新 reader，使刚提交的文档可见
+pub fn matching_entries(req: &QueryRequest, indexed_since: i64, cap: usize) -> Result<Vec<(String, String)>> {
+    let open = indexer::open_cached(&req.index_dir)?;
+    open.reader.reload()?;
+    let searcher = open.reader.searcher();
+    let f = open.fields;
+    let since = RangeQuery::new_i64_bounds("indexed_ts".to_string(), Bound::Included(indexed_since), Bound::Unbounded);
+    let query = BooleanQuery::new(vec![
+        (Occur::Must, build_query(&open.index, &searcher, &f, req, None)?),
+        (Occur::Must, Box::new(ConstScoreQuery::new(Box::new(since), 0.0))),
+    ]);
+    let mut entries = Vec::new();
+    for addr in searcher.search(&query, &DocSetCollector)?.into_iter().take(cap) {
+        let doc: tantivy::TantivyDocument = searcher.doc(addr)?;
+        let text = |field: Field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        entries.push((text(f.path), text(f.name)));
+    }
+    Ok(entries)
+}
+
+pub async fn query(req: QueryRequest) -> Result<SearchResponse> {
+    if req.collections.is_empty() { return query_index(&req); }
+    let cfg = crate::config::read_config().await?;
+    let targets = req
+        .collections
+        .iter()
+        .map(|name| cfg.collection(name).ok_or_else(|| anyhow::anyhow!("索引集合不存在：{}", name)))
+        .collect::<Result<Vec<_>>>()?;
+    federated_query(&req, &targets)
+}
+
+// 多集合检索：并行查询各集合（各取前 offset+limit 条），得分按集合内最高分归一化到 [0, 1] 后合并排序。
+// 合并只覆盖前 MAX_LIMIT 条：offset 超出时返回错误，跨越该上限的页截短，响应中的 limit 为实际条数
+fn federated_query(req: &QueryRequest, targets: &[IndexCollection]) -> Result<SearchResponse> {
+    use rayon::prelude::*;
+    let started = std::time::Instant::now();
+    if req.offset >= MAX_LIMIT { anyhow::bail!("多集合检索最多返回前 {} 条结果，offset {} 超出范围", MAX_LIMIT, req.offset); }
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT - req.offset);
+    let parts: Vec<(String, Result<SearchResponse>)> = targets
+        .par_iter()
+        .map(|c| {
+            let sub = QueryRequest {
+                index_dir: c.index_dir.clone(),
+                offset: 0,
+                limit: Some(req.offset + limit),
+                collections: Vec::new(),
+                ..req.clone()
+            };
+            (c.name.clone(), query_index(&sub))
+        })
+        .collect();
+
+    let mut merged = SearchResponse { total: 0, elapsed_ms: 0, offset: req.offset, limit, fuzzy_applied: false, timed_out: false, results: Vec::new(), analyzed_terms: None, facets: None, did_you_mean: None };
+    let mut facets = Vec::new();
+    let mut errors = Vec::new();
+    for (name, part) in parts {
+        let part = match part {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("federated search: collection '{}' failed: {}", name, e);
+                errors.push(format!("{}：{}", name, e));
+                continue;
+            }
+        };
+        merged.total += part.total;
+        merged.fuzzy_applied |= part.fuzzy_applied;
+        merged.timed_out |= part.timed_out;
+        if merged.analyzed_terms.is_none() { merged.analyzed_terms = part.analyzed_terms; }
+        if merged.did_you_mean.is_none() { merged.did_you_mean = part.did_you_mean; }
+        facets.extend(part.facets);
+        let max = part.results.iter().map(|r| r.score).fold(0.0, f32::max);
+        for mut r in part.results {
+            r.score = if max > 0.0 { r.score / max } else { 0.0 };
+            r.collection = Some(name.clone());
+            merged.results.push(r);
+        }
+    }
+    // 全部集合失败时返回错误；部分失败时返回其余集合的结果
+    if errors.len() == targets.len() { return Err(anyhow::anyhow!(errors.join("；"))); }
+    // 拼写纠正只在全部集合都无结果时有意义
+    if merged.total > 0 { merged.did_you_mean = None; }
+
+    let (sort_field, desc) = parse_sort(req.sort_by.as_deref(), req.sort_order.as_deref());
+    merged.results.sort_by(|a, b| compare_results(a, b, sort_field, desc));
+    merged.results = merged.results.into_iter().skip(req.offset).take(limit).collect();
+    if !facets.is_empty() { merged.facets = Some(crate::facets::merge(facets)); }
+    merged.elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("federated search done: collections={}, total={}, elapsed_ms={}", targets.len(), merged.total, merged.elapsed_ms);
+    Ok(merged)
+}
+
+// 合并排序：与单索引排序一致（名称不区分大小写，缺失值排在最后），相同时按归一化得分降序
+fn compare_results(a: &SearchResult, b: &SearchResult, field: SortField, desc: bool) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    fn missing_last<T: Ord>(a: Option<T>, b: Option<T>, desc: bool) -> Ordering {
+        match (a, b) {
+            (Some(x), Some(y)) => if desc { y.cmp(&x) } else { x.cmp(&y) },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+    let by_field = match field {
+        SortField::Score => Ordering::Equal,
+        SortField::Modified => missing_last(a.modified_ts, b.modified_ts, desc),
+        SortField::Size => missing_last(a.size, b.size, desc),
+        SortField::Name => missing_last(Some(a.name.to_lowercase()), Some(b.name.to_lowercase()), desc),
+        SortField::Path => missing_last(Some(a.path.as_str()), Some(b.path.as_str()), desc),
+    };
+    by_field.then_with(|| b.score.total_cmp(&a.score))
+}
+
+// 旧版本索引的降级检索：普通检索词按其现有的 name/content 字段匹配（各词需全部出现），
+// 字段条件、过滤、排序与拼音等依赖新字段的功能忽略；结果只含旧索引存储的信息
+fn legacy_search(req: &QueryRequest, old: &indexer::LegacyIndex, limit: usize, started: std::time::Instant) -> Result<SearchResponse> {
+    let schema = old.index.schema();
+    let fields: Vec<Field> = ["name", "content"]
+        .iter()
+        .filter_map(|n| schema.get_field(n).ok())
+        .filter(|f| schema.get_field_entry(*f).is_indexed())
+        .collect();
+    let (text_groups, _) = parse_query_language(&req.query)?.split();
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for word in free_text(&text_groups).split_whitespace() {
+        let mut per_field: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for &field in &fields {
+            let mut terms: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+            old.index.tokenizer_for_field(field)?.token_stream(word).process(&mut |t| {
+                terms.push((Occur::Must, Box::new(TermQuery::new(Term::from_field_text(field, &t.text), IndexRecordOption::WithFreqs))));
+            });
+            if !terms.is_empty() { per_field.push((Occur::Should, Box::new(BooleanQuery::new(terms)))); }
+        }
+        if !per_field.is_empty() { clauses.push((Occur::Must, Box::new(BooleanQuery::new(per_field)))); }
+    }
+    let query: Box<dyn Query> = if clauses.is_empty() { Box::new(AllQuery) } else { Box::new(BooleanQuery::new(clauses)) };
+    let searcher = old.reader.searcher();
+    let (top_docs, total) = searcher.search(query.as_ref(), &(TopDocs::with_limit(limit).and_offset(req.offset), Count))?;
+    let mut results = Vec::new();
+    for (score, addr) in top_docs {
+        let doc: TantivyDocument = searcher.doc(addr)?;
+        let value = |name: &str| schema.get_field(name).ok().and_then(|f| doc.get_first(f));
+        let text = |name: &str| value(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+        results.push(SearchResult {
+            path: text("path").unwrap_or_default(),
+            name: text("name").unwrap_or_default(),
+            ext: text("ext").unwrap_or_default(),
+            score,
+            size: value("size").and_then(|v| v.as_u64()),
+            modified_ts: value("modified_ts").and_then(|v| v.as_i64()),
+            summary: text("summary"),
+            kind: text("kind").unwrap_or_else(|| "file".to_string()),
+            child_count: None, raw_path: None, snippet: None, snippet_highlights: Vec::new(), name_highlights: Vec::new(),
+            bm25: None, hit_fields: None, explanation: None, collection: None, open_count: None, last_opened_ts: None,
+        });
+    }
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("search::query done on legacy index: results={}, total={}, elapsed_ms={}", results.len(), total, elapsed_ms);
+    Ok(SearchResponse { total, elapsed_ms, offset: req.offset, limit, fuzzy_applied: false, timed_out: false, results, analyzed_terms: None, facets: None, did_you_mean: None })
+}
+
+// 在单个索引上检索
+fn query_index(req: &QueryRequest) -> Result<SearchResponse> {
+    if req.regex {
+        return crate::name_table::regex_search(req);
+    }
+    let started = std::time::Instant::now();
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    info!("search::query start: q='{}', index='{}', offset={}, limit={}", req.query, req.index_dir, req.offset, limit);
+    // 旧版本索引缺少字段时（按新版本重建完成前）以其现有字段降级检索
+    let open = match indexer::open_cached(&req.index_dir) {
+        Ok(open) => open,
+        Err(e) => match indexer::open_legacy(&req.index_dir)? {
+            Some(old) => return legacy_search(req, &old, limit, started),
+            None => return Err(e),
+        },
+    };
+    let (index, f) = (&open.index, open.fields);
+    let open_ms = started.elapsed().as_millis();
+    let searcher = open.reader.searcher();
+
+    let history = open_history::snapshot();
+    let (sort_field, desc) = parse_sort(req.sort_by.as_deref(), req.sort_order.as_deref());
+    let frecency_weight = if req.query.trim().chars().count() <= SHORT_QUERY_CHARS { FRECENCY_WEIGHT_SHORT } else { FRECENCY_WEIGHT };
+    let frecency_applied = !req.recent && sort_field == SortField::Score && !history.is_empty();
+    let now = chrono::Local::now().timestamp();
+    let run = |query: &dyn Query| -> Result<(Vec<(Score, DocAddress)>, usize)> {
+        if req.recent {
+            recent_search(&searcher, query, &history, limit, req.offset)
+        } else if frecency_applied {
+            frecency_search(&searcher, query, &history, frecency_weight, now, limit, req.offset)
+        } else if sort_field == SortField::Score {
+            Ok(searcher.search(query, &(TopDocs::with_limit(limit).and_offset(req.offset), Count))?)
+        } else {
+            debug!("search::query sort: {:?}, desc={}", sort_field, desc);
+            sorted_search(&searcher, query, sort_field, desc, limit, req.offset)
+        }
+    };
+
+    let fuzzy_opts = FuzzyOptions::new(req.fuzzy_distance, req.fuzzy_prefix_len);
+    let mut fuzzy_applied = req.fuzzy;
+    // 最近打开模式下限定为打开历史中的路径
+    let build = |fuzzy: Option<FuzzyOptions>| -> Result<Box<dyn Query>> {
+        let query = build_query(index, &searcher, &f, req, fuzzy)?;
+        if !req.recent { return Ok(query); }
+        let paths: Vec<Term> = history.keys().map(|p| Term::from_field_text(f.path, p)).collect();
+        Ok(Box::new(BooleanQuery::new(vec![
+            (Occur::Must, query),
+            (Occur::Must, Box::new(ConstScoreQuery::new(Box::new(TermSetQuery::new(paths)), 0.0))),
+        ])))
+    };
+    let mut query = build(req.fuzzy.then_some(fuzzy_opts))?;
+    let (mut top_docs, mut total) = run(query.as_ref())?;
+    if total == 0 && !req.fuzzy && !req.query.trim().is_empty() {
+        query = build(Some(fuzzy_opts))?;
+        (top_docs, total) = run(query.as_ref())?;
+        fuzzy_applied = total > 0;
+        info!("search::query no exact hits; fuzzy retry: total={}", total);
+    }
+    let did_you_mean = if total == 0 && !req.query.trim().is_empty() { crate::suggest::correct_query(&searcher, &f, &req.query)? } else { None };
+    // 片段生成器：由查询中针对 name/content 的词项生成，按字段分词器切分文本后定位命中
+    let content_snippets = SnippetGenerator::create(&searcher, query.as_ref(), f.content).ok().map(|mut g| {
+        g.set_max_num_chars(SNIPPET_MAX_CHARS);
+        g
+    });
+    // 文件名最长 255 字节，片段上限取足够大的值以覆盖完整名称
+    let name_snippets = SnippetGenerator::create(&searcher, query.as_ref(), f.name).ok().map(|mut g| {
+        g.set_max_num_chars(1024);
+        g
+    });
+
     let mut results = Vec::new();
     for (score, doc_address) in top_docs {
         let doc: tantivy::TantivyDocument = searcher.doc(doc_address)?;
-        let path = doc
-            .get_first(f_path)
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-        let name = doc
-            .get_first(f_name)
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-        let ext = doc
-            .get_first(f_ext)
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let size = f_size.and_then(|f| doc.get_first(f)).and_then(|v| v.as_u64());
-        let modified_ts = f_modified.and_then(|f| doc.get_first(f)).and_then(|v| v.as_i64());
-        let summary = f_summary
-            .and_then(|f| doc.get_first(f))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-
-        // 过滤器：扩展名与大小
-        if let Some(filters) = &req.filters {
-            if let Some(exts) = &filters.ext {
-                if !exts.is_empty() && !exts.iter().any(|e| e.eq_ignore_ascii_case(&ext)) {
-                    debug!("filter skip by ext: {}", ext);
-                    continue;
+        let text = |field: Field| doc.get_first(field).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let path = text(f.path).unwrap_or_default();
+        let name = text(f.name).unwrap_or_default();
+        let ext = text(f.ext).unwrap_or_default();
+        let summary = text(f.summary);
+        let kind = text(f.kind).unwrap_or_else(|| "file".to_string());
+        let size = doc.get_first(f.size).and_then(|v| v.as_u64());
+        let modified_ts = doc.get_first(f.modified).and_then(|v| v.as_i64());
+        let child_count = doc.get_first(f.child_count).and_then(|v| v.as_u64());
+        let raw_path = doc.get_first(f.raw_path).and_then(|v| v.as_bytes()).map(hex::encode);
+
+        let (snippet, snippet_highlights) = match (&content_snippets, text(f.content_head)) {
+            (Some(g), Some(head)) => {
+                let sn = g.snippet(&head);
+                if sn.highlighted().is_empty() {
+                    (None, Vec::new())
+                } else {
+                    (Some(sn.fragment().to_string()), utf16_ranges(sn.fragment(), sn.highlighted()))
                 }
             }
-            if let Some(min_s) = filters.min_size {
-                if let Some(sz) = size { if sz < min_s { debug!("filter skip by min_size: {}", sz); continue; } }
-            }
-            if let Some(max_s) = filters.max_size {
-                if let Some(sz) = size { if sz > max_s { debug!("filter skip by max_size: {}", sz); continue; } }
+            _ => (None, Vec::new()),
+        };
+        let name_highlights = match &name_snippets {
+            Some(g) => {
+                let sn = g.snippet(&name);
+                // 片段从首个 token 开始，可能不含名称开头的标点，需换算回名称中的偏移
+                match name.find(sn.fragment()).filter(|_| !sn.is_empty()) {
+                    Some(base) => {
+                        let shifted: Vec<_> = sn.highlighted().iter().map(|r| r.start + base..r.end + base).collect();
+                        utf16_ranges(&name, &shifted)
+                    }
+                    None => Vec::new(),
+                }
             }
+            None => Vec::new(),
+        };
+
+        let opened = history.get(&path);
+        let mut explanation = if req.explain { Some(explain::summarize(&query.explain(&searcher, doc_address)?, label_boost)) } else { None };
+        let hit_fields = explanation.as_ref().map(|e| e.fields.iter().map(|s| s.field.clone()).collect());
+        if let (Some(e), Some(rec), true) = (explanation.as_mut(), opened, frecency_applied) {
+            explain::add_multiplier(e, LABEL_FRECENCY, frecency_boost(rec, now, frecency_weight));
+        }
+        let bm25 = explanation.as_ref().map(|e| e.fields.iter().filter(|s| s.field == "name" || s.field == "content").fold(0.0, |acc, s| acc + s.score));
+
+        results.push(SearchResult {
+            path, name, ext, score, size, modified_ts, summary, kind, child_count, raw_path,
+            snippet, snippet_highlights, name_highlights, bm25, hit_fields, explanation, collection: None,
+            open_count: opened.map(|r| r.count), last_opened_ts: opened.map(|r| r.last_ts),
+        });
+    }
+    let analyzed_terms = req.explain.then(|| explain::analyzed_terms(query.as_ref(), &index.schema()));
+    let facets = if req.facets {
+        let raw = searcher.search(query.as_ref(), &FacetCollector::new())?;
+        let base = req.filters.as_ref().and_then(|flt| flt.path_prefix.as_deref()).map(tokenizer::normalize_path).filter(|p| !p.is_empty());
+        Some(crate::facets::finalize(raw, base))
+    } else {
+        None
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("search::query done: results={}, total={}, elapsed_ms={} (open_ms={})", results.len(), total, elapsed_ms, open_ms);
+    if elapsed_ms > SLOW_QUERY_MS { warn!("slow search: q='{}', elapsed_ms={} exceeds {}ms target", req.query, elapsed_ms, SLOW_QUERY_MS); }
+    Ok(SearchResponse { total, elapsed_ms, offset: req.offset, limit, fuzzy_applied, timed_out: false, results, analyzed_terms, facets, did_you_mean })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{Dictionary, UserWord};
+    use crate::scanner::FileMeta;
+
+    fn file(name: &str) -> FileMeta {
+        FileMeta { path: format!("/docs/{}", name), file_name: name.to_string(), ext: "txt".into(), size: 1, modified_ts: 1, kind: "file".into(), child_count: None, raw_path: None }
+    }
+
+    // 添加用户词后，按旧切分写入的文档（"年度预算审查" 切为 年度预算/审查，重写后也不含新词）仍能以该词检索到
+    #[test]
+    fn user_word_matches_documents_indexed_before_it_was_added() {
+        dictionary::set_in_memory(Dictionary::default());
+        let index = Index::create_in_ram(indexer::build_schema());
+        indexer::register_tokenizers(&index);
+        let f = IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(15_000_000).unwrap();
+        for name in ["年度预算审查.txt", "预算审查意见.txt", "审查预算.txt"] { writer.add_document(indexer::make_doc(&f, &file(name), 0)).unwrap(); }
+        writer.commit().unwrap();
+        let count = |text: &str| {
+            let q = text_clause(&index, &f, text, None, TextScope::Any).unwrap().unwrap();
+            index.reader().unwrap().searcher().search(&q, &Count).unwrap()
+        };
+        assert_eq!(count("预算审查"), 3);
+
+        let before = vec![("预算审查".to_string(), tokenizer::analyze("预算审查"))];
+        let mut words = vec![UserWord { word: "预算审查".into(), freq: None, tag: None, split: None }];
+        dictionary::record_splits(&mut words, &[], &before);
+        dictionary::set_in_memory(Dictionary { words, ..Default::default() });
+        assert_eq!(tokenizer::analyze("预算审查"), vec!["预算审查"]);
+        assert_eq!(tokenizer::analyze("年度预算审查"), vec!["年度预算", "审查"]);
+        // 加入词典前写入的文档仍按原切分的各词匹配，结果与加入前相同
+        assert_eq!(count("预算审查"), 3);
+    }
+
+    // 多个拉丁词的拼音查询要求每个词都匹配，或整体拼接后匹配单个词的全拼
+    #[test]
+    fn pinyin_words_must_all_match() {
+        let index = Index::create_in_ram(indexer::build_schema());
+        indexer::register_tokenizers(&index);
+        let f = IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(15_000_000).unwrap();
+        // 张三.txt、张王报告.txt、三里屯.txt 的拼音变体
+        for (full, initials) in [("zhangsan", "zs"), ("baogao zhang zhangwangbaogao wang", "bg z w zwbg"), ("sanlitun", "slt")] {
+            writer.add_document(tantivy::doc!(f.name_pinyin => full, f.name_initials => initials)).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |text: &str| searcher.search(&pinyin_query(&f, text).unwrap(), &Count).unwrap();
+        assert_eq!(count("zhang"), 2);
+        assert_eq!(count("zhang san"), 1);
+        assert_eq!(count("zhang bg"), 1);
+        assert_eq!(count("zs"), 1);
+        assert_eq!(count("zhang li"), 0);
+        assert!(pinyin_query(&f, "zhang 3").is_none());
+    }
+
+    // 解析结果的简要表示：每组以 " | " 连接 OR 备选项
+    fn parsed(input: &str) -> Vec<String> {
+        let item = |i: &Item| {
+            let s = match &i.clause {
+                Clause::Text { text, phrase, scope } => format!("{:?}:{}{}", scope, text, phrase.map(|p| format!("~{}", p)).unwrap_or_default()),
+                Clause::Ext(exts) => format!("ext:{}", exts.join(",")),
+                Clause::Size(lo, hi) => format!("size:{:?}..{:?}", lo, hi),
+                Clause::Modified(lo, hi) => format!("modified:{:?}..{:?}", lo, hi),
+                Clause::PathUnder(dir) => format!("under:{}", dir),
+                Clause::Path(p) => format!("path:{}", p),
+                Clause::Kind(k) => format!("kind:{}", k),
+            };
+            if i.negated { format!("-{}", s) } else { s }
+        };
+        parse_query_language(input).unwrap().groups.iter().map(|g| g.iter().map(item).collect::<Vec<_>>().join(" | ")).collect()
+    }
+
+    fn syntax_error_at(input: &str) -> usize {
+        parse_query_language(input).unwrap_err().downcast::<QuerySyntaxError>().unwrap().pos
+    }
+
+    #[test]
+    fn parses_words_quotes_and_negation() {
+        assert_eq!(parsed("  budget   report "), ["Any:budget", "Any:report"]);
+        assert_eq!(parsed(r#""annual budget" name:"年度 预算"~3"#), ["Any:annual budget~0", "Name:年度 预算~3"]);
+        assert_eq!(parsed(r#"content:review"#), ["Content:review"]);
+        // 引号内的冒号与空白原样保留
+        assert_eq!(parsed(r#""ext:pdf OR x""#), ["Any:ext:pdf OR x~0"]);
+        assert_eq!(parsed("-draft -ext:log - x"), ["-Any:draft", "-ext:log", "Any:-", "Any:x"]);
+        // 未知前缀按普通词处理
+        assert_eq!(parsed(r"http://a C:\x"), ["Any:http://a", r"Any:C:\x"]);
+    }
+
+    #[test]
+    fn or_binds_adjacent_conditions() {
+        assert_eq!(parsed("a b OR c d"), ["Any:a", "Any:b | Any:c", "Any:d"]);
+        assert_eq!(parsed("a OR b OR -ext:tmp"), ["Any:a | Any:b | -ext:tmp"]);
+        // 小写 or 与引号中的 OR 是普通词
+        assert_eq!(parsed(r#"a or b "OR""#), ["Any:a", "Any:or", "Any:b", "Any:OR~0"]);
+    }
+
+    #[test]
+    fn parses_field_values() {
+        assert_eq!(parsed("ext:.PDF,docx,"), ["ext:pdf,docx"]);
+        assert_eq!(parsed("EXT:md"), ["ext:md"]);
+        assert_eq!(parsed("size:>10mb"), [format!("size:Included({})..Unbounded", 10 * 1024 * 1024 + 1)]);
+        assert_eq!(parsed("size:<=1k"), ["size:Unbounded..Excluded(1025)"]);
+        assert_eq!(parsed("size:1kb..2kb"), ["size:Included(1024)..Excluded(2049)"]);
+        assert_eq!(parsed("size:512"), ["size:Included(512)..Excluded(513)"]);
+        let (lo, hi) = parse_date("2024-03").unwrap();
+        assert_eq!(hi - lo, 31 * 86400);
+        assert_eq!(parsed("modified:2024-03"), [format!("modified:Included({})..Excluded({})", lo, hi)]);
+        assert_eq!(parsed("modified:2024-12.."), [format!("modified:Included({})..Unbounded", parse_date("2024-12").unwrap().0)]);
+        assert_eq!(parsed("kind:DIR"), ["kind:dir"]);
+        // 绝对路径按目录层级匹配（去掉末尾分隔符），其他取值为子串
+        assert_eq!(parsed("path:/home/me/docs/"), ["under:/home/me/docs"]);
+        assert_eq!(parsed(r"path:C:\Users\me"), [format!("under:{}", tokenizer::normalize_path(r"C:\Users\me"))]);
+        assert_eq!(parsed("path:projects/ -path:/tmp"), ["path:projects/", "-under:/tmp"]);
+    }
+
+    #[test]
+    fn reports_malformed_queries() {
+        assert_eq!(syntax_error_at(r#"a "open"#), 3);
+        assert_eq!(syntax_error_at("OR a"), 1);
+        assert_eq!(syntax_error_at("a OR OR b"), 6);
+        assert_eq!(syntax_error_at("a OR"), 3);
+        assert_eq!(syntax_error_at("ext:"), 5);
+        assert_eq!(syntax_error_at("ext:,"), 5);
+        assert_eq!(syntax_error_at("size:>lots"), 7);
+        assert_eq!(syntax_error_at("size:1mb..x"), 11);
+        assert_eq!(syntax_error_at("modified:2024-13"), 10);
+        assert_eq!(syntax_error_at("kind:pipe"), 6);
+        assert_eq!(syntax_error_at("path:/"), 6);
+        assert_eq!(syntax_error_at(r#"ext:"pdf"~2"#), 5);
+        assert_eq!(syntax_error_at(r#""a b"~x"#), 6);
+        assert!(parse_query_language("").unwrap().groups.is_empty());
+    }
+
+    // 写入临时目录中的索引（query_index 按目录打开并缓存）
+    fn temp_index(tag: &str, names: &[&str]) -> String {
+        let dir = std::env::temp_dir().join(format!("searchevery-test-{}-{}", tag, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dir = dir.to_string_lossy().to_string();
+        let index = indexer::open_or_create(&dir).unwrap();
+        let f = IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(15_000_000).unwrap();
+        for name in names { writer.add_document(indexer::make_doc(&f, &file(name), 0)).unwrap(); }
+        writer.commit().unwrap();
+        dir
+    }
+
+    fn request(dir: &str, query: &str, extra: serde_json::Value) -> QueryRequest {
+        let mut v = serde_json::json!({"query": query, "index_dir": dir});
+        if let (Some(obj), Some(more)) = (v.as_object_mut(), extra.as_object()) { obj.extend(more.clone()); }
+        serde_json::from_value(v).unwrap()
+    }
+
+    // 旧版本索引在重建完成前以现有字段降级检索；writer 被占用时的删除推迟到写入结束后补上，替换后生效
+    #[test]
+    fn outdated_index_is_searchable_until_rebuild_is_promoted() {
+        let dir = std::env::temp_dir().join(format!("searchevery-test-legacy-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_string_lossy().to_string();
+        let rebuild = indexer::rebuild_dir(&dir);
+        let _ = std::fs::remove_dir_all(&rebuild);
+        // 早期版本的 schema：默认分词器，无 path_tree、kind 等字段
+        let mut b = Schema::builder();
+        let (path, name) = (b.add_text_field("path", TEXT | STORED), b.add_text_field("name", TEXT | STORED));
+        b.add_text_field("content", TEXT);
+        let size = b.add_u64_field("size", STORED);
+        let old = Index::create_in_dir(&dir, b.build()).unwrap();
+        let mut w: tantivy::IndexWriter<TantivyDocument> = old.writer(15_000_000).unwrap();
+        let names = ["budget report.txt", "budget.xlsx", "notes.md"];
+        for n in names { w.add_document(tantivy::doc!(path => format!("/docs/{}", n), name => n, size => 1u64)).unwrap(); }
+        w.commit().unwrap();
+        drop(w);
+
+        let hits = |q: &str| query_index(&request(&dir, q, serde_json::json!({}))).unwrap();
+        assert!(indexer::open_cached(&dir).is_err());
+        assert_eq!(hits("budget").total, 2);
+        let r = hits("budget report");
+        assert_eq!((r.total, r.results[0].path.as_str(), r.results[0].size), (1, "/docs/budget report.txt", Some(1)));
+
+        let index = indexer::open_or_create(&dir).unwrap();
+        assert!(indexer::is_rebuilding(&dir));
+        let f = IndexFields::from_schema(&index.schema()).unwrap();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = index.writer(15_000_000).unwrap();
+        for n in names { writer.add_document(indexer::make_doc(&f, &file(n), 0)).unwrap(); }
+        writer.commit().unwrap();
+        indexer::delete_entry(&rebuild, "/docs/notes.md", None).unwrap();
+        drop(writer);
+        assert_eq!(indexer::apply_pending_deletes(&rebuild).unwrap(), 1);
+        // 仍在重建：继续使用旧索引
+        assert_eq!(hits("notes").total, 1);
+
+        indexer::promote_rebuild(&dir).unwrap();
+        assert!(!indexer::is_rebuilding(&dir) && !std::path::Path::new(&rebuild).exists());
+        assert_eq!(hits("budget").total, 2);
+        assert_eq!(hits("notes").total, 0);
+        assert_eq!(hits("kind:file ext:txt").total, 2);
+        // 旧索引的文件已删除，目录中只剩新索引登记的文件
+        let managed: Vec<String> = serde_json::from_str(&std::fs::read_to_string(std::path::Path::new(&dir).join(".managed.json")).unwrap()).unwrap();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let n = entry.unwrap().file_name().to_string_lossy().to_string();
+            assert!(managed.contains(&n) || n == ".managed.json" || n.starts_with(".tantivy-"), "leftover {}", n);
+        }
+        indexer::invalidate_cached(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // 多集合检索只合并前 MAX_LIMIT 条：跨越上限的页截短，超出上限的 offset 返回错误
+    #[test]
+    fn federated_paging_stops_at_the_merge_window() {
+        let names = ["report.txt", "report.md", "report.pdf"];
+        let targets: Vec<IndexCollection> = ["fed_a", "fed_b"]
+            .iter()
+            .map(|tag| serde_json::from_value(serde_json::json!({"name": tag, "index_dir": temp_index(tag, &names)})).unwrap())
+            .collect();
+        let page = |offset: usize, limit: usize| federated_query(&request("", "report", serde_json::json!({"offset": offset, "limit": limit})), &targets);
+        let first = page(0, 4).unwrap();
+        assert_eq!((first.total, first.limit, first.results.len()), (6, 4, 4));
+        assert!(first.results.iter().all(|r| r.collection.is_some()));
+        assert_eq!(page(4, 4).unwrap().results.len(), 2);
+        let edge = page(MAX_LIMIT - 2, 10).unwrap();
+        assert_eq!((edge.offset, edge.limit, edge.results.len()), (MAX_LIMIT - 2, 2, 0));
+        assert!(page(MAX_LIMIT, 10).is_err());
+        for t in &targets {
+            indexer::invalidate_cached(&t.index_dir);
+            let _ = std::fs::remove_dir_all(&t.index_dir);
+        }
+    }
+
+    // 重写已有条目沿用首次写入时间，关注提醒按该时间只取回新条目
+    #[test]
+    fn rewrites_keep_first_indexed_time() {
+        let dir = temp_index("first_ts", &["invoice_a.pdf"]);
+        let open = indexer::open_cached(&dir).unwrap();
+        let f = open.fields;
+        let prior = open.index.reader().unwrap().searcher();
+        let mut writer: tantivy::IndexWriter<TantivyDocument> = open.index.writer(15_000_000).unwrap();
+        for (name, now) in [("invoice_a.pdf", 100), ("invoice_b.pdf", 100)] {
+            let fm = file(name);
+            writer.delete_term(f.key_term(&fm.path, fm.raw_path.as_deref()));
+            let ts = indexer::first_indexed_ts(&prior, &f, &fm).unwrap_or(now);
+            writer.add_document(indexer::make_doc(&f, &fm, ts)).unwrap();
         }
+        writer.commit().unwrap();
+        drop(writer);
+        let fresh = matching_entries(&request(&dir, "invoice", serde_json::json!({})), 50, 10).unwrap();
+        assert_eq!(fresh, vec![("/docs/invoice_b.pdf".to_string(), "invoice_b.pdf".to_string())]);
+        assert_eq!(matching_entries(&request(&dir, "invoice", serde_json::json!({})), 0, 10).unwrap().len(), 2);
+        indexer::invalidate_cached(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-        results.push(SearchResult { path, name, ext, score, size, modified_ts, summary });
+    // 精确检索无结果时自动以默认参数容错重试，并在响应中标明
+    #[test]
+    fn zero_hits_fall_back_to_fuzzy_name_search() {
+        let dir = temp_index("fuzzy", &["report_final.pdf", "budget.xlsx"]);
+        let exact = query_index(&request(&dir, "report", serde_json::json!({}))).unwrap();
+        assert_eq!((exact.total, exact.fuzzy_applied), (1, false));
+        let retried = query_index(&request(&dir, "reprot", serde_json::json!({}))).unwrap();
+        assert_eq!((retried.total, retried.fuzzy_applied), (1, true));
+        assert_eq!(retried.results[0].name, "report_final.pdf");
+        // 重试仍无结果时不标记为容错
+        let none = query_index(&request(&dir, "qqqzzz", serde_json::json!({}))).unwrap();
+        assert_eq!((none.total, none.fuzzy_applied), (0, false));
+        // 默认距离为 1；显式指定距离 2 时才能命中两处编辑
+        let far = query_index(&request(&dir, "bdugte", serde_json::json!({}))).unwrap();
+        assert_eq!(far.total, 0);
+        let far = query_index(&request(&dir, "bdugte", serde_json::json!({"fuzzy": true, "fuzzy_distance": 2}))).unwrap();
+        assert_eq!((far.total, far.fuzzy_applied), (1, true));
+        indexer::invalidate_cached(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
     }
-    info!("search::query done: results={}", results.len());
-    Ok(results)
-}
\ No newline at end of file
+}
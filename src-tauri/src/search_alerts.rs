@@ -0,0 +1,252 @@
+use anyhow::Result;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{self, AppConfig};
+use crate::search::{self, QueryRequest};
+use crate::search_history::{self, SavedSearch};
+
+// 关注提醒：索引提交后，对标记为关注的已保存搜索只检查首次写入时间（indexed_ts）不早于上次检查的文档，
+// 出现此前未匹配过的条目时计入该搜索的未读列表，并发出 saved_search_alert 事件。
+// 检查由扫描流水线（运行中至多每分钟一次，结束时一次）与手动建索引触发；没有文件系统监听，
+// 两次扫描之间新增的文件要等下一次扫描写入索引后才会提醒。重写已有条目沿用原 indexed_ts，不会重复提醒。
+// 状态存放在应用配置目录的 search_alerts.json，只在未读或关注列表变化时写入；
+// 仅检查时间变化时不落盘，重启后从较早的时间重新检查，重复的由 recent 去除
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertMatch {
+    pub path: String,
+    pub name: String,
+    // 发现时间（Unix 秒）
+    pub ts: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WatchState {
+    // 各索引目录上次检查的时间（Unix 秒）
+    #[serde(default)]
+    checked: HashMap<String, i64>,
+    // 近期已匹配的路径哈希 -> 发现时间（Unix 秒）：只用于去除重叠检查窗口内的重复，早于窗口的随检查清理
+    #[serde(default)]
+    recent: HashMap<u64, i64>,
+    #[serde(default)]
+    unread: Vec<AlertMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchAlerts {
+    pub id: u64,
+    pub name: String,
+    pub unread: Vec<AlertMatch>,
+}
+
+// 单次检查取回的新匹配上限；未读列表上限（超出时丢弃最早的）
+const MAX_CHECK_MATCHES: usize = 5_000;
+const MAX_UNREAD: usize = 500;
+// 文档在写入（生成 indexed_ts）之后才提交，检查时向前多看一段时间，重复的由 recent 去除
+const CHECK_OVERLAP_SECS: i64 = 60;
+
+static STATE: Lazy<Mutex<Option<HashMap<u64, WatchState>>>> = Lazy::new(|| Mutex::new(None));
+// 正在进行的检查：Some 时新的检查请求只记下索引目录，由进行中的那次依次补做
+static IN_FLIGHT: Lazy<Mutex<Option<HashSet<String>>>> = Lazy::new(|| Mutex::new(None));
+
+fn path_hash(path: &str) -> u64 {
+    let digest = Sha256::digest(path.as_bytes());
+    u64::from_le_bytes(digest[..8].try_into().unwrap_or_default())
+}
+
+impl WatchState {
+    // 记录已匹配的路径，返回是否为新路径
+    fn see(&mut self, path: &str, now: i64) -> bool {
+        self.recent.insert(path_hash(path), now).is_none()
+    }
+
+    // 发现时间早于所有索引下一次检查窗口的记录不会再被查到，可以清除
+    fn prune(&mut self) {
+        let Some(oldest) = self.checked.values().min().copied() else { return };
+        self.recent.retain(|_, t| *t >= oldest - CHECK_OVERLAP_SECS);
+    }
+}
+
+fn state_path() -> PathBuf {
+    tauri::api::path::app_config_dir(&tauri::Config::default())
+        .unwrap_or_else(|| PathBuf::from("./"))
+        .join("search_alerts.json")
+}
+
+fn load() -> HashMap<u64, WatchState> {
+    let p = state_path();
+    if !p.exists() { return HashMap::new(); }
+    match fs::read_to_string(&p).map_err(anyhow::Error::from).and_then(|s| Ok(serde_json::from_str::<HashMap<u64, WatchState>>(&s)?)) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("search_alerts: failed to load {:?}: {}", p, e);
+            HashMap::new()
+        }
+    }
+}
+
+// f 返回 (结果, 是否需要写入)
+fn with_state<R>(f: impl FnOnce(&mut HashMap<u64, WatchState>) -> Result<(R, bool)>) -> Result<R> {
+    let mut guard = STATE.lock();
+    let st = guard.get_or_insert_with(load);
+    let (r, dirty) = f(st)?;
+    if !dirty { return Ok(r); }
+    let p = state_path();
+    if let Some(dir) = p.parent() { fs::create_dir_all(dir)?; }
+    fs::write(p, serde_json::to_string(st)?)?;
+    Ok(r)
+}
+
+// 已保存搜索实际检索的索引目录：指定了集合时按配置解析，否则为请求中的 index_dir
+fn target_dirs(req: &QueryRequest, cfg: &AppConfig) -> Vec<String> {
+    if req.collections.is_empty() { return vec![req.index_dir.clone()]; }
+    req.collections.iter().filter_map(|name| cfg.collection(name)).map(|c| c.index_dir).collect()
+}
+
+fn sub_request(req: &QueryRequest, index_dir: &str) -> QueryRequest {
+    QueryRequest { index_dir: index_dir.to_string(), offset: 0, collections: Vec::new(), ..req.clone() }
+}
+
+// 开始关注：此后只提醒首次写入晚于此刻的条目；重叠窗口内已有的匹配先记下，避免下一次检查误报
+pub async fn watch(s: &SavedSearch) -> Result<()> {
+    let cfg = config::read_config().await?;
+    let mut st = WatchState::default();
+    for dir in target_dirs(&s.request, &cfg) {
+        let now = chrono::Local::now().timestamp();
+        let matches = search::matching_entries(&sub_request(&s.request, &dir), now - CHECK_OVERLAP_SECS, MAX_CHECK_MATCHES)?;
+        for (path, _) in matches { st.see(&path, now); }
+        st.checked.insert(dir, now);
+    }
+    info!("search_alerts: watching saved search {} ('{}'), recent={}", s.id, s.name, st.recent.len());
+    with_state(|all| {
+        all.insert(s.id, st);
+        Ok(((), true))
+    })
+}
+
+// 取消关注或删除已保存的搜索时清除其状态
+pub fn forget(id: u64) -> Result<()> {
+    with_state(|all| {
+        Ok(((), all.remove(&id).is_some()))
+    })
+}
+
+// 索引提交后调用：检查关注的已保存搜索中涉及 index_dir 的那些。
+// 同一时间只进行一次检查，期间到来的请求按索引目录合并，由进行中的那次随后补做
+pub async fn check(app: &AppHandle, index_dir: &str) {
+    {
+        let mut guard = IN_FLIGHT.lock();
+        if let Some(pending) = guard.as_mut() {
+            pending.insert(index_dir.to_string());
+            return;
+        }
+        *guard = Some(HashSet::new());
+    }
+    let mut dir = index_dir.to_string();
+    loop {
+        check_dir(app, &dir).await;
+        let mut guard = IN_FLIGHT.lock();
+        let next = guard.as_mut().and_then(|pending| {
+            let d = pending.iter().next().cloned()?;
+            pending.remove(&d);
+            Some(d)
+        });
+        match next {
+            Some(d) => dir = d,
+            None => {
+                *guard = None;
+                break;
+            }
+        }
+    }
+}
+
+async fn check_dir(app: &AppHandle, index_dir: &str) {
+    let watched: Vec<SavedSearch> = search_history::list_saved().into_iter().filter(|s| s.watch).collect();
+    if watched.is_empty() { return; }
+    let cfg = match config::read_config().await {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("search_alerts: read config failed: {}", e);
+            return;
+        }
+    };
+    for s in watched {
+        let dirs = target_dirs(&s.request, &cfg);
+        if !dirs.iter().any(|d| d == index_dir) { continue; }
+        match check_one(&s, index_dir, &dirs) {
+            Ok(Some((new, unread_count))) => {
+                info!("search_alerts: saved search {} ('{}') has {} new matches", s.id, s.name, new.len());
+                let _ = app.emit_all("saved_search_alert", json!({"id": s.id, "name": s.name, "matches": new, "unread_count": unread_count}));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("search_alerts: check saved search {} failed: {}", s.id, e),
+        }
+    }
+}
+
+// 返回新匹配及未读总数；没有新匹配时返回 None
+// target_dirs 为该搜索当前涉及的索引目录，其余目录的检查时间随之清除
+fn check_one(s: &SavedSearch, index_dir: &str, target_dirs: &[String]) -> Result<Option<(Vec<AlertMatch>, usize)>> {
+    let now = chrono::Local::now().timestamp();
+    let since = STATE.lock().get_or_insert_with(load).get(&s.id).and_then(|st| st.checked.get(index_dir).copied());
+    // 此前未检查过该索引（如集合为后来添加）：视为开始关注，只记录不提醒
+    let lo = since.unwrap_or(now) - CHECK_OVERLAP_SECS;
+    let matches = search::matching_entries(&sub_request(&s.request, index_dir), lo, MAX_CHECK_MATCHES)?;
+    with_state(|all| {
+        let st = all.entry(s.id).or_default();
+        st.checked.insert(index_dir.to_string(), now);
+        st.checked.retain(|d, _| target_dirs.contains(d));
+        st.prune();
+        let mut new = Vec::new();
+        let mut added = false;
+        for (path, name) in matches {
+            if !st.see(&path, now) { continue; }
+            added = true;
+            if since.is_some() { new.push(AlertMatch { path, name, ts: now }); }
+        }
+        // 首次检查该索引时需落盘记录基线，否则只在出现新匹配时写入
+        if new.is_empty() { return Ok((None, added && since.is_none())); }
+        st.unread.extend(new.iter().cloned());
+        let excess = st.unread.len().saturating_sub(MAX_UNREAD);
+        st.unread.drain(..excess);
+        Ok((Some((new, st.unread.len())), true))
+    })
+}
+
+// 各关注搜索的未读匹配（最新在前）
+pub fn list() -> Vec<SearchAlerts> {
+    let mut guard = STATE.lock();
+    let all = guard.get_or_insert_with(load);
+    search_history::list_saved()
+        .into_iter()
+        .filter(|s| s.watch)
+        .map(|s| SearchAlerts {
+            id: s.id,
+            unread: all.get(&s.id).map(|st| st.unread.iter().rev().cloned().collect()).unwrap_or_default(),
+            name: s.name,
+        })
+        .collect()
+}
+
+// 标记已读：paths 为空时清空该搜索的全部未读
+pub fn mark_read(id: u64, paths: Option<Vec<String>>) -> Result<()> {
+    with_state(|all| {
+        let Some(st) = all.get_mut(&id) else { return Ok(((), false)) };
+        let before = st.unread.len();
+        match paths {
+            Some(paths) => st.unread.retain(|m| !paths.contains(&m.path)),
+            None => st.unread.clear(),
+        }
+        Ok(((), st.unread.len() != before))
+    })
+}
@@ -0,0 +1,243 @@
+use anyhow::Result;
+use log::warn;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::search::{QueryRequest, SearchFilters};
+
+// 检索历史（有上限，最新在前）与已保存的搜索（名称 + 完整请求，可置顶与排序），
+// 分别存放在应用配置目录的 search_history.json 与 saved_searches.json
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub query: String,
+    #[serde(default)]
+    pub filters: Option<SearchFilters>,
+    pub ts: i64,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: u64,
+    pub name: String,
+    pub request: QueryRequest,
+    #[serde(default)]
+    pub pinned: bool,
+    // 显示顺序（升序）；置顶的排在前面
+    #[serde(default)]
+    pub order: u32,
+    pub created_ts: i64,
+    #[serde(default)]
+    pub last_run_ts: Option<i64>,
+    // 关注：索引提交后检查新写入的文档，出现新匹配时提醒（见 search_alerts）
+    #[serde(default)]
+    pub watch: bool,
+}
+
+// 历史条数上限
+const MAX_HISTORY: usize = 200;
+// 输入即搜会逐键产生请求：间隔不超过该秒数、且与上一条互为前缀（过滤条件相同）时覆盖上一条
+const TYPING_MERGE_SECS: i64 = 10;
+
+// 记录检索只更新内存，写盘推迟该时长后合并进行（输入即搜时连续的请求只写一次）
+const HISTORY_FLUSH_DELAY: Duration = Duration::from_secs(5);
+
+static HISTORY: Lazy<Mutex<Option<Vec<HistoryEntry>>>> = Lazy::new(|| Mutex::new(None));
+// 已安排延迟写盘
+static FLUSH_SCHEDULED: AtomicBool = AtomicBool::new(false);
+static SAVED: Lazy<Mutex<Option<Vec<SavedSearch>>>> = Lazy::new(|| Mutex::new(None));
+
+fn store_path(file: &str) -> PathBuf {
+    tauri::api::path::app_config_dir(&tauri::Config::default())
+        .unwrap_or_else(|| PathBuf::from("./"))
+        .join(file)
+}
+
+fn load<T: DeserializeOwned + Default>(file: &str) -> T {
+    let p = store_path(file);
+    if !p.exists() { return T::default(); }
+    match fs::read_to_string(&p).map_err(anyhow::Error::from).and_then(|s| Ok(serde_json::from_str(&s)?)) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("search_history: failed to load {:?}: {}", p, e);
+            T::default()
+        }
+    }
+}
+
+fn save<T: Serialize>(file: &str, value: &T) -> Result<()> {
+    let p = store_path(file);
+    if let Some(dir) = p.parent() { fs::create_dir_all(dir)?; }
+    fs::write(p, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+const HISTORY_FILE: &str = "search_history.json";
+const SAVED_FILE: &str = "saved_searches.json";
+
+fn with_history<R>(f: impl FnOnce(&mut Vec<HistoryEntry>) -> Result<R>) -> Result<R> {
+    let mut guard = HISTORY.lock();
+    let h = guard.get_or_insert_with(|| load(HISTORY_FILE));
+    let r = f(h)?;
+    save(HISTORY_FILE, h)?;
+    Ok(r)
+}
+
+fn with_saved<R>(f: impl FnOnce(&mut Vec<SavedSearch>) -> Result<R>) -> Result<R> {
+    let mut guard = SAVED.lock();
+    let s = guard.get_or_insert_with(|| load(SAVED_FILE));
+    let r = f(s)?;
+    save(SAVED_FILE, s)?;
+    Ok(r)
+}
+
+// 安排一次延迟写盘；已安排时不重复
+fn schedule_history_flush() {
+    if FLUSH_SCHEDULED.swap(true, Ordering::SeqCst) { return; }
+    std::thread::spawn(|| {
+        std::thread::sleep(HISTORY_FLUSH_DELAY);
+        FLUSH_SCHEDULED.store(false, Ordering::SeqCst);
+        let guard = HISTORY.lock();
+        if let Some(h) = guard.as_ref() {
+            if let Err(e) = save(HISTORY_FILE, h) { warn!("search_history: flush failed: {}", e); }
+        }
+    });
+}
+
+fn same_filters(a: &Option<SearchFilters>, b: &Option<SearchFilters>) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+// ---------------- 检索历史 ----------------
+
+// 记录一次检索（仅首页；空查询不记录）；与已有条目相同的查询移到最前。
+// 只更新内存，由 schedule_history_flush 延迟写盘
+pub fn record(req: &QueryRequest, total: usize) -> Result<()> {
+    let query = req.query.trim();
+    if query.is_empty() || req.offset > 0 { return Ok(()); }
+    let now = chrono::Local::now().timestamp();
+    {
+        let mut guard = HISTORY.lock();
+        let h = guard.get_or_insert_with(|| load(HISTORY_FILE));
+        if let Some(last) = h.first() {
+            let typing = now - last.ts <= TYPING_MERGE_SECS && (query.starts_with(last.query.as_str()) || last.query.starts_with(query));
+            if typing && same_filters(&last.filters, &req.filters) { h.remove(0); }
+        }
+        h.retain(|e| !(e.query == query && same_filters(&e.filters, &req.filters)));
+        h.insert(0, HistoryEntry { query: query.to_string(), filters: req.filters.clone(), ts: now, total });
+        h.truncate(MAX_HISTORY);
+    }
+    schedule_history_flush();
+    Ok(())
+}
+
+pub fn list_history() -> Vec<HistoryEntry> {
+    HISTORY.lock().get_or_insert_with(|| load(HISTORY_FILE)).clone()
+}
+
+pub fn clear_history() -> Result<()> {
+    with_history(|h| {
+        h.clear();
+        Ok(())
+    })
+}
+
+// ---------------- 已保存的搜索 ----------------
+
+fn sorted(list: &[SavedSearch]) -> Vec<SavedSearch> {
+    let mut v = list.to_vec();
+    v.sort_by_key(|s| (!s.pinned, s.order, s.id));
+    v
+}
+
+fn find(list: &mut [SavedSearch], id: u64) -> Result<&mut SavedSearch> {
+    list.iter_mut().find(|s| s.id == id).ok_or_else(|| anyhow::anyhow!("已保存的搜索不存在：{}", id))
+}
+
+// 置顶的在前，其余按 order
+pub fn list_saved() -> Vec<SavedSearch> {
+    sorted(SAVED.lock().get_or_insert_with(|| load(SAVED_FILE)))
+}
+
+// 保存搜索：分页位置不保存，重新执行时从第一页开始
+pub fn save_search(name: &str, mut request: QueryRequest) -> Result<SavedSearch> {
+    let name = name.trim();
+    if name.is_empty() { return Err(anyhow::anyhow!("名称不能为空")); }
+    request.offset = 0;
+    with_saved(|list| {
+        let s = SavedSearch {
+            id: list.iter().map(|s| s.id).max().unwrap_or(0) + 1,
+            name: name.to_string(),
+            request,
+            pinned: false,
+            order: list.iter().map(|s| s.order + 1).max().unwrap_or(0),
+            created_ts: chrono::Local::now().timestamp(),
+            last_run_ts: None,
+            watch: false,
+        };
+        list.push(s.clone());
+        Ok(s)
+    })
+}
+
+pub fn rename_saved(id: u64, name: &str) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() { return Err(anyhow::anyhow!("名称不能为空")); }
+    with_saved(|list| {
+        find(list, id)?.name = name.to_string();
+        Ok(())
+    })
+}
+
+pub fn delete_saved(id: u64) -> Result<()> {
+    with_saved(|list| {
+        let before = list.len();
+        list.retain(|s| s.id != id);
+        if list.len() == before { return Err(anyhow::anyhow!("已保存的搜索不存在：{}", id)); }
+        Ok(())
+    })
+}
+
+pub fn set_pinned(id: u64, pinned: bool) -> Result<()> {
+    with_saved(|list| {
+        find(list, id)?.pinned = pinned;
+        Ok(())
+    })
+}
+
+// 设置关注；正则与最近打开模式不按文档写入时间检索，不支持关注
+pub fn set_watch(id: u64, watch: bool) -> Result<SavedSearch> {
+    with_saved(|list| {
+        let s = find(list, id)?;
+        if watch && (s.request.regex || s.request.recent) { return Err(anyhow::anyhow!("关注仅支持全文检索")); }
+        s.watch = watch;
+        Ok(s.clone())
+    })
+}
+
+// 按给定的 id 顺序重新编号；未列出的保持相对顺序排在其后
+pub fn reorder_saved(ids: &[u64]) -> Result<()> {
+    with_saved(|list| {
+        let rest: Vec<u64> = sorted(list).into_iter().map(|s| s.id).filter(|id| !ids.contains(id)).collect();
+        for (order, id) in ids.iter().chain(rest.iter()).enumerate() {
+            if let Some(s) = list.iter_mut().find(|s| s.id == *id) { s.order = order as u32; }
+        }
+        Ok(())
+    })
+}
+
+// 取出已保存搜索的请求以重新执行，并记录执行时间
+pub fn take_for_run(id: u64) -> Result<QueryRequest> {
+    with_saved(|list| {
+        let s = find(list, id)?;
+        s.last_run_ts = Some(chrono::Local::now().timestamp());
+        Ok(s.request.clone())
+    })
+}
@@ -0,0 +1,173 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tantivy::schema::Field;
+use tantivy::{Searcher, Term};
+use log::debug;
+
+use crate::fuzzy;
+use crate::indexer;
+use crate::search_history;
+
+// 输入建议：最近的检索（前缀匹配）、name 与 content 词典中以最后一个词为前缀的词（按文档频率排序），
+// 以及按编辑距离在词典中查找的拼写纠正（"您是不是要找"）
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestRequest {
+    pub query: String,
+    pub index_dir: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    // 同时返回拼写纠正（用于无结果时的提示；输入过程中最后一个词往往不完整，默认不计算）
+    #[serde(default)]
+    pub correct: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    // 补全后的完整查询
+    pub text: String,
+    // "history"（最近的检索）| "term"（词典补全）
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_freq: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestResponse {
+    pub suggestions: Vec<Suggestion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did_you_mean: Option<String>,
+}
+
+const DEFAULT_LIMIT: usize = 8;
+const MAX_LIMIT: usize = 50;
+// 建议中最近检索的条数上限
+const MAX_HISTORY_SUGGESTIONS: usize = 3;
+// 每个段内按前缀扫描的词数上限（如单个字母前缀可能展开出大量词）
+const MAX_SCAN_TERMS: usize = 10_000;
+// 拼写纠正：短于该长度的词不纠正；较长的词允许更大的编辑距离
+const MIN_CORRECT_CHARS: usize = 3;
+const LONG_WORD_CHARS: usize = 6;
+
+// 最后一个词之前的部分、词前缀（排除号、字段名与引号）与待补全的小写前缀
+struct Partial<'a> {
+    head: &'a str,
+    lead: &'a str,
+    prefix: String,
+    // 仅在指定字段中补全；None 为 name 与 content
+    field: Option<&'a str>,
+}
+
+fn split_partial(query: &str) -> Option<Partial<'_>> {
+    if query.ends_with(char::is_whitespace) { return None; }
+    let start = query.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+    let (head, word) = query.split_at(start);
+    let mut rest = word.trim_start_matches('-');
+    let mut field = None;
+    if let Some((name, after)) = rest.split_once(':') {
+        // 其他字段（扩展名、大小、时间等）不从词典补全
+        if name != "name" && name != "content" { return None; }
+        field = Some(name);
+        rest = after;
+    }
+    let rest = rest.trim_start_matches('"');
+    if rest.is_empty() || rest.contains('"') { return None; }
+    let lead = &word[..word.len() - rest.len()];
+    Some(Partial { head, lead, prefix: rest.to_lowercase(), field })
+}
+
+// 各段词典中以 prefix 开头的词及其文档频率（各段累加）
+fn prefix_terms(searcher: &Searcher, field: Field, prefix: &str, out: &mut HashMap<String, u64>) -> Result<()> {
+    for seg in searcher.segment_readers() {
+        let inverted = seg.inverted_index(field)?;
+        let mut terms = inverted.terms().range().ge(prefix.as_bytes()).into_stream()?;
+        let mut scanned = 0;
+        while terms.advance() && scanned < MAX_SCAN_TERMS {
+            scanned += 1;
+            let Ok(key) = std::str::from_utf8(terms.key()) else { continue };
+            if !key.starts_with(prefix) { break; }
+            *out.entry(key.to_string()).or_default() += terms.value().doc_freq as u64;
+        }
+    }
+    Ok(())
+}
+
+fn doc_freq(searcher: &Searcher, fields: &[Field], word: &str) -> Result<u64> {
+    let mut n = 0;
+    for &f in fields { n += searcher.doc_freq(&Term::from_field_text(f, word))?; }
+    Ok(n)
+}
+
+// 在词典中查找与 word 编辑距离最小（相同时文档频率最高）的词；假定首字符输入正确，只扫描同一首字符的词
+fn closest_term(searcher: &Searcher, fields: &[Field], word: &str) -> Result<Option<String>> {
+    let max_distance = if word.chars().count() >= LONG_WORD_CHARS { 2 } else { 1 };
+    let Some(first) = word.chars().next() else { return Ok(None) };
+    let mut candidates = HashMap::new();
+    for &f in fields { prefix_terms(searcher, f, first.encode_utf8(&mut [0; 4]), &mut candidates)?; }
+    let best = candidates
+        .into_iter()
+        .map(|(term, freq)| (fuzzy::distance(word, &term), freq, term))
+        .filter(|(d, _, _)| *d >= 1 && *d <= max_distance)
+        .min_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+    Ok(best.map(|(_, _, term)| term))
+}
+
+// 拼写纠正：逐个替换词典中不存在的普通词（字段条件、短语、排除与 OR 保持不变）；无可纠正的词时返回 None
+pub fn correct_query(searcher: &Searcher, f: &indexer::IndexFields, query: &str) -> Result<Option<String>> {
+    let fields = [f.name, f.content];
+    let mut changed = false;
+    let mut words = Vec::new();
+    for w in query.split_whitespace() {
+        let plain = w != "OR" && !w.starts_with('-') && !w.contains(':') && !w.contains('"');
+        let lower = w.to_lowercase();
+        if plain && lower.chars().count() >= MIN_CORRECT_CHARS && lower.chars().all(char::is_alphanumeric) && doc_freq(searcher, &fields, &lower)? == 0 {
+            if let Some(term) = closest_term(searcher, &fields, &lower)? {
+                debug!("suggest: correct '{}' -> '{}'", w, term);
+                words.push(term);
+                changed = true;
+                continue;
+            }
+        }
+        words.push(w.to_string());
+    }
+    Ok(changed.then(|| words.join(" ")))
+}
+
+pub fn suggest(req: &SuggestRequest) -> Result<SuggestResponse> {
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let lower = req.query.trim().to_lowercase();
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    if !lower.is_empty() {
+        for h in search_history::list_history() {
+            if suggestions.len() >= MAX_HISTORY_SUGGESTIONS.min(limit) { break; }
+            let q = h.query.to_lowercase();
+            if q != lower && q.starts_with(&lower) && !suggestions.iter().any(|s| s.text == h.query) {
+                suggestions.push(Suggestion { text: h.query, kind: "history".to_string(), doc_freq: None });
+            }
+        }
+    }
+
+    let open = indexer::open_cached(&req.index_dir)?;
+    let searcher = open.reader.searcher();
+    let f = open.fields;
+    if let Some(p) = split_partial(&req.query) {
+        let fields = match p.field {
+            Some("name") => vec![f.name],
+            Some(_) => vec![f.content],
+            None => vec![f.name, f.content],
+        };
+        let mut terms = HashMap::new();
+        for field in fields { prefix_terms(&searcher, field, &p.prefix, &mut terms)?; }
+        let mut ranked: Vec<(String, u64)> = terms.into_iter().filter(|(t, _)| *t != p.prefix).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (term, freq) in ranked {
+            if suggestions.len() >= limit { break; }
+            let text = format!("{}{}{}", p.head, p.lead, term);
+            if suggestions.iter().any(|s| s.text == text) { continue; }
+            suggestions.push(Suggestion { text, kind: "term".to_string(), doc_freq: Some(freq) });
+        }
+    }
+    let did_you_mean = if req.correct { correct_query(&searcher, &f, &req.query)? } else { None };
+    Ok(SuggestResponse { suggestions, did_you_mean })
+}
@@ -0,0 +1,156 @@
+use jieba_rs::{Jieba, TokenizeMode};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+use crate::dictionary::UserWord;
+
+// 中文分词器：基于 jieba 的搜索模式切分（长词同时产出其中的短词），
+// 英文与数字按词切分；空白与纯标点不作为 token
+pub const JIEBA_TOKENIZER: &str = "jieba";
+
+// 词典加载较慢（数百毫秒），首次使用时初始化；用户词典变更时整体替换（见 dictionary）
+static JIEBA: Lazy<RwLock<Jieba>> = Lazy::new(|| RwLock::new(with_user_words(&crate::dictionary::user_words())));
+
+// 内置词典加上用户词
+fn with_user_words(words: &[UserWord]) -> Jieba {
+    let mut jieba = Jieba::new();
+    for w in words { jieba.add_word(&w.word, w.freq, w.tag.as_deref()); }
+    jieba
+}
+
+// 按新的用户词列表重建分词词典（在锁外构建，替换时才持写锁）
+pub fn reload_user_words(words: &[UserWord]) {
+    let jieba = with_user_words(words);
+    *JIEBA.write() = jieba;
+}
+
+// 使该词能被切分出来的建议词频（导出 "词 词频 词性" 格式时补全未指定的词频）
+pub fn suggest_freq(word: &str) -> usize {
+    JIEBA.read().suggest_freq(word)
+}
+
+// 精确模式分词（不产出重叠短词），供拼音生成等需要词边界的场景使用
+pub fn cut(text: &str) -> Vec<&str> {
+    JIEBA.read().cut(text, false)
+}
+
+// 与检索一致的切分结果：同一位置只保留最长的词，转小写（判断用户词变更是否改变某个词的切分）
+pub fn analyze(text: &str) -> Vec<String> {
+    let mut tokenizer = JiebaTokenizer;
+    let mut stream = tokenizer.token_stream(text);
+    let mut words: Vec<(usize, usize, String)> = Vec::new();
+    while stream.advance() {
+        let t = stream.token();
+        let len = t.offset_to - t.offset_from;
+        match words.last_mut() {
+            Some(w) if w.0 == t.position => { if len >= w.1 { *w = (t.position, len, t.text.to_lowercase()); } }
+            _ => words.push((t.position, len, t.text.to_lowercase())),
+        }
+    }
+    words.into_iter().map(|(_, _, w)| w).collect()
+}
+
+#[derive(Clone, Default)]
+pub struct JiebaTokenizer;
+
+// 预先切分好的 token 序列（jieba 与路径分词器共用）
+pub struct VecTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Tokenizer for JiebaTokenizer {
+    type TokenStream<'a> = VecTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> VecTokenStream {
+        // jieba 返回字符下标，tantivy 需要字节偏移
+        let mut char_bytes: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        char_bytes.push(text.len());
+        let words: Vec<_> = JIEBA
+            .read()
+            .tokenize(text, TokenizeMode::Search, true)
+            .into_iter()
+            .filter(|t| t.word.chars().any(|c| c.is_alphanumeric()))
+            .collect();
+        // 位置按词计算：搜索模式在每个词之后紧跟其本身，之前先产出其中的短词，
+        // 短词与所属的词共用同一位置，使短语查询按词相邻判断。倒序扫描时被当前词包含的即为其短词
+        let mut positions = vec![0usize; words.len()];
+        let mut word_count = 0;
+        let mut current = (usize::MAX, usize::MAX);
+        for (i, t) in words.iter().enumerate().rev() {
+            if !(t.start >= current.0 && t.end <= current.1) {
+                current = (t.start, t.end);
+                word_count += 1;
+            }
+            positions[i] = word_count;
+        }
+        let tokens = words
+            .iter()
+            .zip(positions)
+            .map(|(t, p)| Token {
+                offset_from: char_bytes[t.start],
+                offset_to: char_bytes[t.end],
+                position: word_count - p,
+                text: t.word.to_string(),
+                position_length: 1,
+            })
+            .collect();
+        VecTokenStream { tokens, index: 0 }
+    }
+}
+
+impl TokenStream for VecTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+// 路径层级分词器：每个祖先目录及完整路径各作为一个 token（"/a/b/c.txt" → "/a"、"/a/b"、"/a/b/c.txt"），
+// 使 "位于某目录之下" 成为单个 term 查询
+pub const PATH_TOKENIZER: &str = "path_tree";
+
+// 路径归一化：分隔符统一为 '/'，去掉末尾分隔符；Windows 路径不区分大小写，统一转小写
+pub fn normalize_path(path: &str) -> String {
+    let p = path.replace('\\', "/");
+    #[cfg(target_os = "windows")]
+    let p = p.to_lowercase();
+    p.trim_end_matches('/').to_string()
+}
+
+#[derive(Clone, Default)]
+pub struct PathTokenizer;
+
+impl Tokenizer for PathTokenizer {
+    type TokenStream<'a> = VecTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> VecTokenStream {
+        let norm = normalize_path(text);
+        let mut tokens = Vec::new();
+        let ends = norm.match_indices('/').map(|(i, _)| i).filter(|&i| i > 0).chain(std::iter::once(norm.len()));
+        for end in ends {
+            if norm[..end].ends_with('/') { continue; }
+            tokens.push(Token {
+                offset_from: 0,
+                offset_to: end,
+                position: tokens.len(),
+                text: norm[..end].to_string(),
+                position_length: 1,
+            });
+        }
+        VecTokenStream { tokens, index: 0 }
+    }
+}
@@ -1,21 +1,21 @@
 import { useEffect, useMemo, useRef, useState } from 'react'
 // API 调用封装
-import { readConfig, writeConfig, resetConfig, scanPaths, scanPathsProgress, buildInvertedIndex, buildInvertedIndexProgress, searchQuery, openLocation, scanAndIndexPipeline, startAutoScanNow, diagnosticsReport as fetchDiagnostics, detectDuplicates, deleteFileAndIndex } from './services/api'
+import { readConfig, writeConfig, resetConfig, scanPaths, scanPathsProgress, buildInvertedIndex, buildInvertedIndexProgress, searchQuery, nameSearch, openLocation, scanAndIndexPipeline, startAutoScanNow, cancelScan, diagnosticsReport as fetchDiagnostics, detectDuplicates, deleteFileAndIndex, clearOpenHistory, listSearchHistory, suggest, listSavedSearches, saveSearch, runSavedSearch, renameSavedSearch, deleteSavedSearch, pinSavedSearch, fromRustQuery, watchSavedSearch, listSearchAlerts, markSearchAlertsRead, getDictionary, setSynonyms, removeUserWords, importUserDictionary, exportUserDictionary } from './services/api'
 import type { DiagnosticsReport } from './types'
 // Tauri 文件/文件夹选择对话框
 import { open as openDialog } from '@tauri-apps/api/dialog'
 import { onIndexProgress, onIndexDone, onEvent } from './services/events'
-import type { FileMeta, SearchResult, AppConfig, PageKey, ThemeKey, DupGroup } from './types'
+import type { FileMeta, SearchResult, SearchRequest, SearchResponse, Facets, AppConfig, PageKey, ThemeKey, DupGroup, SearchHistoryEntry, SavedSearch, SearchAlerts, SavedSearchAlertPayload, Suggestion, Dictionary } from './types'
 import ThemeDropdown from './components/ThemeDropdown'
 import AboutPage from './pages/AboutPage'
 import { formatBytes, toBytesMb } from './utils/size'
 import { formatTs, formatRelativeTs } from './utils/date'
 import { isTauri } from './utils/env'
 import { parseCSV } from './utils/string'
-import { highlight } from './utils/highlight'
+import { highlight, highlightRanges } from './utils/highlight'
 import { shortenPath } from './utils/path'
 import useTauriEvents from './hooks/useTauriEvents'
-import { PATH_MAX_LEN } from './constants/ui'
+import { PATH_MAX_LEN, PAGE_SIZE } from './constants/ui'
 import { logError } from './services/logging'
 import useGlobalErrorLogging from './hooks/useGlobalErrorLogging'
 import useFocusDebug from './hooks/useFocusDebug'
@@ -30,19 +30,54 @@ export default function App() {
   const [scanDir, setScanDir] = useState('')
   const [query, setQuery] = useState('')
   const [results, setResults] = useState<SearchResult[]>([])
+  // 分页：命中总数与最近一次请求（用于加载更多）
+  const [total, setTotal] = useState(0)
+  const [lastReq, setLastReq] = useState<SearchRequest | null>(null)
   const [busy, setBusy] = useState(false)
   const [msg, setMsg] = useState('')
   const [enableContentParse, setEnableContentParse] = useState(false)
   const [page, setPage] = useState<PageKey>('search')
   const [theme, setTheme] = useState<ThemeKey>('eye')
   const [autoScanEnabled, setAutoScanEnabled] = useState<boolean>(true)
+  const [respectIgnoreFiles, setRespectIgnoreFiles] = useState<boolean>(false)
+  // 最近一次读取的完整配置：保存时保留设置页未展示的字段
+  const [baseCfg, setBaseCfg] = useState<AppConfig | null>(null)
   const searchInputRef = useRef<HTMLInputElement | null>(null)
   const [viewMode, setViewMode] = useState<'table' | 'card'>('table')
   const [selected, setSelected] = useState<SearchResult | null>(null)
   const [extFilter, setExtFilter] = useState('')
+  const [kindFilter, setKindFilter] = useState<'' | 'file' | 'dir'>('')
+  // 修改时间：最近 N 天（空为不限）；路径前缀：仅检索该目录下
+  const [timeFilter, setTimeFilter] = useState('')
+  // 分面：全文检索时随首页结果返回；点击年份/月份分面后以其区间作为时间过滤（优先于最近 N 天）
+  const [facets, setFacets] = useState<Facets | undefined>(undefined)
+  const [facetTime, setFacetTime] = useState<{ from: number; to: number; label: string } | null>(null)
+  const [pathPrefix, setPathPrefix] = useState('')
+  // 检索的索引集合：空为当前索引，'*' 为全部集合，其余为单个集合名称
+  const [collectionFilter, setCollectionFilter] = useState('')
+  // 文件名容错检索（精确无结果时后端也会自动启用）
+  const [fuzzy, setFuzzy] = useState(false)
+  // 排序解释：详情中展示命中字段与得分明细
+  const [explain, setExplain] = useState(false)
+  const [analyzedTerms, setAnalyzedTerms] = useState<SearchResponse['analyzed_terms']>(undefined)
+  // 检索历史（输入框候选）与已保存的搜索
+  const [history, setHistory] = useState<SearchHistoryEntry[]>([])
+  // 输入建议（全文模式下随输入更新；为空时候选为检索历史）与无结果时的拼写纠正
+  const [suggestions, setSuggestions] = useState<Suggestion[]>([])
+  const [didYouMean, setDidYouMean] = useState<string | undefined>(undefined)
+  // 用户词典（导入/导出为 jieba 格式文本）与同义词组（编辑时每行一组，逗号分隔）
+  const [dictionary, setDictionary] = useState<Dictionary | null>(null)
+  const [dictText, setDictText] = useState('')
+  const [synonymText, setSynonymText] = useState('')
+  const [dictMsg, setDictMsg] = useState('')
+  const [savedSearches, setSavedSearches] = useState<SavedSearch[]>([])
+  // 关注搜索的未读新匹配（按已保存搜索 id）
+  const [alerts, setAlerts] = useState<Record<number, SearchAlerts['unread']>>({})
+  // 检索方式：全文（分词检索）| 文件名（内存子串匹配，逐键即时返回）| 正则（匹配文件名或完整路径）| 最近打开（可为空查询）
+  const [searchMode, setSearchMode] = useState<'fulltext' | 'name' | 'regex_name' | 'regex_path' | 'recent'>('fulltext')
   const [minSize, setMinSize] = useState<string>('')
   const [maxSize, setMaxSize] = useState<string>('')
-  const [sortBy, setSortBy] = useState<'score' | 'time' | 'size'>('score')
+  const [sortBy, setSortBy] = useState<'score' | 'time' | 'size' | 'name' | 'path'>('score')
   const [sortDir, setSortDir] = useState<'asc' | 'desc'>('desc')
   const [lang, setLang] = useState<'zh' | 'en'>('zh')
   const [idxCurrent, setIdxCurrent] = useState(0)
@@ -81,13 +116,18 @@ export default function App() {
 
   useEffect(() => {
     // 排序变更时仅滚动视图，不强制焦点
-    const activeBtn = sortBy === 'score' ? sortScoreRef.current : (sortBy === 'time' ? sortTimeRef.current : sortSizeRef.current)
+    const activeBtn = sortBy === 'score' ? sortScoreRef.current : (sortBy === 'time' ? sortTimeRef.current : (sortBy === 'size' ? sortSizeRef.current : null))
     const scrollContainer = sortTabsRef.current
     if (activeBtn && scrollContainer) {
       activeBtn.scrollIntoView({ behavior: 'smooth', inline: 'center', block: 'nearest' })
     }
   }, [sortBy])
 
+  // 排序在后端完成（分页结果需全局有序），排序变更时重新检索
+  useEffect(() => {
+    if (lastReq) doSearch()
+  }, [sortBy, sortDir])
+
   useEffect(() => {
     if (!inTauri) {
       setMsg('当前在浏览器预览模式。后端命令不可用，请使用 Tauri 开发模式进行端到端联调。')
@@ -109,6 +149,10 @@ export default function App() {
       setIdxRunning(false)
       setMsg('索引构建完成')
     }),
+    () => onEvent<SavedSearchAlertPayload>('saved_search_alert', (payload) => {
+      setAlerts(prev => ({ ...prev, [payload.id]: [...payload.matches.slice().reverse(), ...(prev[payload.id] ?? [])] }))
+      setMsg(`「${payload.name}」出现 ${payload.matches.length} 条新匹配`)
+    }),
     () => onEvent<{ total?: number }>('scan_done', (payload) => {
       if (payload?.total != null) {
         setIdxTotal(payload.total)
@@ -133,6 +177,7 @@ export default function App() {
     ;(async () => {
       try {
         const cfg = await readConfig()
+        setBaseCfg(cfg)
         setIndexDir(cfg.index_dir || 'indexes/main')
         setRoots((cfg.scan_roots || []).join(', '))
         setExclude((cfg.exclude_patterns || []).join(', '))
@@ -142,6 +187,9 @@ export default function App() {
         if (typeof (cfg as any).auto_scan_enabled === 'boolean') {
           setAutoScanEnabled((cfg as any).auto_scan_enabled)
         }
+        setRespectIgnoreFiles(!!cfg.respect_ignore_files)
+        setSavedSearches(await listSavedSearches())
+        await refreshAlerts()
         setMsg('已加载默认配置')
       } catch (e) {
         // 忽略读取失败，保留前端默认
@@ -162,11 +210,27 @@ export default function App() {
   useEffect(() => {
     if (!inTauri || page !== 'search') return
     const handler = setTimeout(() => {
-      if (query.trim()) doSearch()
-    }, 250)
+      if (query.trim() || searchMode === 'recent') doSearch()
+    }, searchMode === 'name' ? 30 : 250)
     return () => clearTimeout(handler)
     // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [query, extFilter, minSize, maxSize, page, inTauri])
+  }, [query, extFilter, kindFilter, timeFilter, facetTime, pathPrefix, collectionFilter, fuzzy, explain, searchMode, minSize, maxSize, page, inTauri])
+
+  // 进入设置页时加载用户词典与同义词
+  useEffect(() => {
+    if (inTauri && page === 'settings') refreshDictionary().catch(e => logError(e, 'get_dictionary'))
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [page, inTauri])
+
+  // 输入建议（120ms 防抖）：仅全文模式；过期的响应丢弃
+  useEffect(() => {
+    if (!inTauri || searchMode !== 'fulltext' || !query.trim()) { setSuggestions([]); return }
+    let stale = false
+    const handler = setTimeout(() => {
+      suggest(query, indexDir).then(r => { if (!stale) setSuggestions(r.suggestions) }).catch(() => {})
+    }, 120)
+    return () => { stale = true; clearTimeout(handler) }
+  }, [query, indexDir, searchMode, inTauri])
 
   async function doScan() {
     if (!inTauri) return
@@ -178,6 +242,9 @@ export default function App() {
         exclude_patterns: parseCSV(exclude),
         max_file_size_mb: 500,
         follow_symlinks: false,
+        respect_ignore_files: respectIgnoreFiles,
+        one_file_system: baseCfg?.one_file_system ?? true,
+        skip_mount_kinds: baseCfg?.skip_mount_kinds,
       }
       setScanCount(0)
       setScanDir('')
@@ -227,7 +294,13 @@ export default function App() {
     setIdxRunning(true)
     setMsg('扫描并构建索引中...')
     try {
-      const opts = { roots: parseCSV(roots), exclude_patterns: parseCSV(exclude) }
+      const opts = {
+        roots: parseCSV(roots),
+        exclude_patterns: parseCSV(exclude),
+        respect_ignore_files: respectIgnoreFiles,
+        one_file_system: baseCfg?.one_file_system ?? true,
+        skip_mount_kinds: baseCfg?.skip_mount_kinds,
+      }
       const indexOpts = { indexDir, enable_content_parse: enableContentParse }
       await scanAndIndexPipeline(opts, indexOpts)
     } catch (e: any) {
@@ -245,14 +318,17 @@ export default function App() {
     setMsg('保存配置中...')
     try {
       const cfg: AppConfig = {
+        ...(baseCfg ?? {}),
         search_mode: 'inverted',
         scan_roots: parseCSV(roots),
         exclude_patterns: parseCSV(exclude),
         index_dir: indexDir,
         path_max_len: pathMaxLen,
         auto_scan_enabled: autoScanEnabled,
+        respect_ignore_files: respectIgnoreFiles,
       }
       await writeConfig(cfg)
+      setBaseCfg(cfg)
       setMsg('默认配置已保存')
     } catch (e: any) {
       logError(e, 'write_config')
@@ -268,6 +344,7 @@ export default function App() {
     setMsg('恢复默认配置中...')
     try {
       const cfg = await resetConfig()
+      setBaseCfg(cfg)
       setIndexDir(cfg.index_dir || 'indexes/main')
       setRoots((cfg.scan_roots || []).join(', '))
       setExclude((cfg.exclude_patterns || []).join(', '))
@@ -277,6 +354,7 @@ export default function App() {
       if (typeof (cfg as any).auto_scan_enabled === 'boolean') {
         setAutoScanEnabled((cfg as any).auto_scan_enabled)
       }
+      setRespectIgnoreFiles(!!cfg.respect_ignore_files)
       setMsg('已恢复默认配置')
     } catch (e: any) {
       logError(e, 'reset_config')
@@ -296,17 +374,27 @@ export default function App() {
       const maxVal = maxSize.trim() ? Number(maxSize) : undefined
       const minBytes = (minVal !== undefined && !Number.isNaN(minVal)) ? toBytesMb(minVal) : undefined
       const maxBytes = (maxVal !== undefined && !Number.isNaN(maxVal)) ? toBytesMb(maxVal) : undefined
-      const filters = (extArr.length || minBytes !== undefined || maxBytes !== undefined) ? {
+      const since = timeFilter ? Math.floor(Date.now() / 1000) - Number(timeFilter) * 86400 : undefined
+      const filters = (extArr.length || minBytes !== undefined || maxBytes !== undefined || kindFilter || since !== undefined || facetTime || pathPrefix.trim()) ? {
         ext: extArr.length ? extArr : undefined,
         min_size: minBytes,
         max_size: maxBytes,
+        kind: kindFilter || undefined,
+        time_range: facetTime ? [facetTime.from, facetTime.to - 1] as [number, number] : (since !== undefined ? [since, null] as [number, null] : undefined),
+        path_prefix: pathPrefix.trim() || undefined,
       } : null
-      const req = { query, filters, indexDir }
-      const res = await searchQuery(req)
-      setResults(res)
-      setMsg(`返回 ${res.length} 条结果`)
+      const collections = collectionFilter === '*' ? ['default', ...(baseCfg?.collections ?? []).map(c => c.name)] : (collectionFilter ? [collectionFilter] : undefined)
+      const req: SearchRequest = { query, filters, indexDir, offset: 0, limit: PAGE_SIZE, sortBy: sortBy === 'time' ? 'modified' : sortBy, sortOrder: sortDir, fuzzy, explain, facets: searchMode === 'fulltext', regex: searchMode.startsWith('regex'), regexTarget: searchMode === 'regex_path' ? 'path' : 'name', recent: searchMode === 'recent', collections }
+      const res = searchMode === 'name' ? await nameSearch(req) : await searchQuery(req)
+      setResults(res.results)
+      setTotal(res.total)
+      setAnalyzedTerms(res.analyzed_terms)
+      setFacets(res.facets)
+      setDidYouMean(res.did_you_mean)
+      setLastReq(req)
+      setMsg(`共 ${res.total} 条结果（${res.elapsed_ms} ms）${res.fuzzy_applied && !fuzzy ? '，精确匹配无结果，已使用容错匹配' : ''}${res.timed_out ? '，正则匹配超时，仅显示部分结果' : ''}`)
       setPage('search')
-      setSelected(res[0] ?? null)
+      setSelected(res.results[0] ?? null)
     } catch (e: any) {
       logError(e, 'search_query', { query, extFilter, indexDir })
       setMsg(e?.toString?.() ?? '检索失败')
@@ -315,9 +403,178 @@ export default function App() {
     }
   }
 
+  async function refreshDictionary() {
+    const d = await getDictionary()
+    setDictionary(d)
+    setSynonymText(d.synonyms.map(g => g.join(', ')).join('\n'))
+  }
+
+  async function doImportDictionary(replace: boolean) {
+    if (!inTauri || !dictText.trim()) return
+    if (replace && !window.confirm('替换现有的全部用户词？')) return
+    setBusy(true)
+    setDictMsg('更新用户词典中...')
+    try {
+      const u = await importUserDictionary(dictText, replace)
+      setDictMsg(`用户词 ${u.words} 个${u.retokenized ? `，已按新词典重写 ${u.retokenized} 个文档` : ''}${u.pending ? `；${u.pending} 个索引正在写入，将在其扫描结束后重写` : ''}`)
+      setDictText('')
+      await refreshDictionary()
+    } catch (e: any) {
+      logError(e, 'import_user_dictionary')
+      setDictMsg(e?.toString?.() ?? '导入失败')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function doExportDictionary() {
+    try {
+      setDictText(await exportUserDictionary())
+      setDictMsg('已导出到文本框，可复制保存')
+    } catch (e: any) {
+      logError(e, 'export_user_dictionary')
+    }
+  }
+
+  async function doRemoveUserWord(word: string) {
+    setBusy(true)
+    try {
+      const u = await removeUserWords([word])
+      setDictMsg(`已删除 ${word}${u.retokenized ? `，已重写 ${u.retokenized} 个文档` : ''}${u.pending ? `；${u.pending} 个索引将在扫描结束后重写` : ''}`)
+      await refreshDictionary()
+    } catch (e: any) {
+      logError(e, 'remove_user_words', { word })
+      setDictMsg(e?.toString?.() ?? '删除失败')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function doSaveSynonyms() {
+    const groups = synonymText.split('\n').map(l => l.split(/[,，]/).map(w => w.trim()).filter(Boolean)).filter(g => g.length > 0)
+    try {
+      const n = await setSynonyms(groups)
+      setDictMsg(`已保存 ${n} 组同义词`)
+      await refreshDictionary()
+    } catch (e: any) {
+      logError(e, 'set_synonyms')
+      setDictMsg(e?.toString?.() ?? '保存失败')
+    }
+  }
+
+  async function refreshSaved() {
+    setSavedSearches(await listSavedSearches())
+  }
+
+  async function refreshAlerts() {
+    const list = await listSearchAlerts()
+    setAlerts(Object.fromEntries(list.map(a => [a.id, a.unread])))
+  }
+
+  async function doWatchSaved(s: SavedSearch) {
+    try {
+      await watchSavedSearch(s.id, !s.watch)
+      await refreshSaved()
+      await refreshAlerts()
+      setMsg(s.watch ? `已取消关注：${s.name}` : `已关注：${s.name}，出现新匹配时提醒`)
+    } catch (e: any) {
+      logError(e, 'watch_saved_search', { id: s.id })
+      setMsg(e?.toString?.() ?? '设置失败')
+    }
+  }
+
+  // 查看关注搜索的新匹配：以结果列表展示并标记为已读
+  async function doShowAlerts(s: SavedSearch) {
+    const unread = alerts[s.id] ?? []
+    setResults(unread.map(m => ({ path: m.path, name: m.name, ext: m.name.includes('.') ? m.name.split('.').pop()!.toLowerCase() : '', score: 1, kind: 'file' })))
+    setTotal(unread.length)
+    setFacets(undefined)
+    setLastReq(null)
+    setSelected(null)
+    setMsg(`「${s.name}」有 ${unread.length} 条新匹配`)
+    await markSearchAlertsRead(s.id).catch((e) => logError(e, 'mark_search_alerts_read'))
+    await refreshAlerts()
+  }
+
+  // 保存当前检索（沿用最近一次请求的完整条件）
+  async function doSaveSearch() {
+    if (!inTauri || !lastReq) return
+    const name = window.prompt('保存搜索的名称', lastReq.query)
+    if (!name?.trim()) return
+    try {
+      await saveSearch(name, lastReq)
+      await refreshSaved()
+      setMsg(`已保存搜索：${name}`)
+    } catch (e: any) {
+      logError(e, 'save_search')
+      setMsg(e?.toString?.() ?? '保存失败')
+    }
+  }
+
+  // 重新执行已保存的搜索：结果由后端按保存时的请求返回，界面上的查询同步为保存的查询
+  async function doRunSaved(s: SavedSearch) {
+    if (!inTauri) return
+    setBusy(true)
+    try {
+      const res = await runSavedSearch(s.id)
+      setQuery(s.request.query)
+      setResults(res.results)
+      setTotal(res.total)
+      setAnalyzedTerms(res.analyzed_terms)
+      setFacets(res.facets)
+      setLastReq(fromRustQuery(s.request))
+      setMsg(`「${s.name}」共 ${res.total} 条结果（${res.elapsed_ms} ms）`)
+      setSelected(res.results[0] ?? null)
+      await refreshSaved()
+    } catch (e: any) {
+      logError(e, 'run_saved_search', { id: s.id })
+      setMsg(e?.toString?.() ?? '检索失败')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function doRenameSaved(s: SavedSearch) {
+    const name = window.prompt('重命名', s.name)
+    if (!name?.trim() || name === s.name) return
+    await renameSavedSearch(s.id, name).catch((e) => logError(e, 'rename_saved_search'))
+    await refreshSaved()
+  }
+
+  async function doDeleteSaved(s: SavedSearch) {
+    if (!window.confirm(`删除已保存的搜索「${s.name}」？`)) return
+    await deleteSavedSearch(s.id).catch((e) => logError(e, 'delete_saved_search'))
+    await refreshSaved()
+  }
+
+  async function doPinSaved(s: SavedSearch) {
+    await pinSavedSearch(s.id, !s.pinned).catch((e) => logError(e, 'pin_saved_search'))
+    await refreshSaved()
+  }
+
+  // 加载下一页：沿用上次的查询与过滤条件
+  async function loadMore() {
+    if (!inTauri || !lastReq) return
+    setBusy(true)
+    try {
+      const req = { ...lastReq, offset: results.length, facets: false }
+      const res = searchMode === 'name' ? await nameSearch(req) : await searchQuery(req)
+      setResults(prev => [...prev, ...res.results])
+      setTotal(res.total)
+      setMsg(`已加载 ${results.length + res.results.length}/${res.total} 条结果`)
+    } catch (e: any) {
+      logError(e, 'search_query', { query: lastReq.query, offset: results.length })
+      setMsg(e?.toString?.() ?? '加载失败')
+    } finally {
+      setBusy(false)
+    }
+  }
+
   function clearQuery() {
     setQuery('')
     setResults([])
+    setTotal(0)
+    setLastReq(null)
     setSelected(null)
     setMsg('查询已清空')
   }
@@ -332,18 +589,6 @@ export default function App() {
     setMsg('筛选与视图已重置为默认')
   }
 
-  const sortedResults = useMemo(() => {
-    const arr = [...results]
-    const factor = sortDir === 'asc' ? 1 : -1
-    arr.sort((a, b) => {
-      if (sortBy === 'score') return (a.score - b.score) * factor
-      if (sortBy === 'time') return ((a.modified_ts ?? 0) - (b.modified_ts ?? 0)) * factor
-      if (sortBy === 'size') return ((a.size ?? 0) - (b.size ?? 0)) * factor
-      return 0
-    })
-    return arr
-  }, [results, sortBy, sortDir])
-
   function Header() {
     // 头部仅保留品牌与主题下拉，去掉查询输入框
     return (
@@ -397,17 +642,25 @@ export default function App() {
                   value={query}
                   onChange={e => setQuery(e.target.value)}
                   onKeyDown={e => { if (e.key === 'Enter') doSearch() }}
+                  onFocus={() => { if (inTauri) listSearchHistory().then(setHistory).catch(() => {}) }}
+                  list="search-history"
                   onBlur={(e) => {
                     // 临时调试：记录输入框失焦时的下一个焦点元素
                     const t = (e.relatedTarget as HTMLElement | null)
                     // eslint-disable-next-line no-console
                     console.log('[search-input:blur] next=', t)
                   }}
-                  placeholder="搜索您的一切"
+                  placeholder="搜索您的一切，如：报告 ext:pdf,docx size:>1mb modified:2024"
                   style={{ width: '100%', padding: '10px 14px 10px 36px', height: 40, border: '1px solid var(--border)', borderRadius: 10, background: 'transparent', color: 'var(--text)' }}
                 />
               </div>
+              <datalist id="search-history">
+                {suggestions.length
+                  ? suggestions.map(s => <option key={`${s.kind}-${s.text}`} value={s.text}>{s.kind === 'history' ? '最近检索' : `${s.doc_freq ?? 0} 个文件`}</option>)
+                  : history.map(h => <option key={`${h.ts}-${h.query}`} value={h.query}>{`${h.total} 条`}</option>)}
+              </datalist>
               <button onClick={doSearch} disabled={busy || !inTauri} style={{ height: 40, padding: '0 18px', borderRadius: 10, background: 'var(--primary)', color: 'white', border: '1px solid var(--primary)' }}>搜一搜</button>
+              <button className="ghost" onClick={doSaveSearch} disabled={busy || !inTauri || !lastReq} style={{ height: 40 }}>保存搜索</button>
             </div>
           </div>
           {/* 顶部筛选行已下移至内容区，与表格左对齐 */}
@@ -443,6 +696,42 @@ export default function App() {
                 <option value="zip,rar,7z">压缩包（zip/rar/7z）</option>
                 <option value="log">log</option>
               </select>
+              <select className="chip-input" value={kindFilter} onChange={e => setKindFilter(e.target.value as '' | 'file' | 'dir')} style={{ width: 110 }}>
+                <option value="">全部条目</option>
+                <option value="file">仅文件</option>
+                <option value="dir">仅文件夹</option>
+              </select>
+              <select className="chip-input" value={timeFilter} onChange={e => setTimeFilter(e.target.value)} style={{ width: 110 }}>
+                <option value="">修改时间</option>
+                <option value="1">最近一天</option>
+                <option value="7">最近一周</option>
+                <option value="30">最近一月</option>
+                <option value="365">最近一年</option>
+              </select>
+              <input className="chip-input" value={pathPrefix} onChange={e => setPathPrefix(e.target.value)} placeholder="限定目录" style={{ width: 140 }} />
+              {!!baseCfg?.collections?.length && (
+                <select className="chip-input" value={collectionFilter} onChange={e => setCollectionFilter(e.target.value)} style={{ width: 120 }} title="文件名即时检索仅使用当前索引">
+                  <option value="">当前索引</option>
+                  <option value="*">全部集合</option>
+                  <option value="default">default</option>
+                  {baseCfg.collections.map(c => <option key={c.name} value={c.name}>{c.name}</option>)}
+                </select>
+              )}
+              <select className="chip-input" value={searchMode} onChange={e => setSearchMode(e.target.value as typeof searchMode)} style={{ width: 110 }}>
+                <option value="fulltext">全文检索</option>
+                <option value="name">文件名即时</option>
+                <option value="regex_name">正则（文件名）</option>
+                <option value="regex_path">正则（路径）</option>
+                <option value="recent">最近打开</option>
+              </select>
+              <label className="checkbox">
+                <input type="checkbox" checked={fuzzy} onChange={e => setFuzzy(e.target.checked)} />
+                容错匹配
+              </label>
+              <label className="checkbox">
+                <input type="checkbox" checked={explain} onChange={e => setExplain(e.target.checked)} />
+                解释排序
+              </label>
             </div>
             <div className="row">
               <div className="view-toggle">
@@ -495,6 +784,8 @@ export default function App() {
                   <option value="score">综合</option>
                   <option value="time">时间</option>
                   <option value="size">大小</option>
+                  <option value="name">名称</option>
+                  <option value="path">路径</option>
                 </select>
                 <button className="ghost" onClick={() => setSortDir(sortDir === 'asc' ? 'desc' : 'asc')}>{sortDir === 'asc' ? '升序' : '降序'}</button>
                 <span className="divider" />
@@ -502,6 +793,39 @@ export default function App() {
               </div>
             </div>
           </div>
+          {savedSearches.length > 0 && (
+            <div className="facets">
+              {savedSearches.map(s => (
+                <span key={s.id} className={s.pinned ? 'chip active' : 'chip'}>
+                  <button className="chip-link" onClick={() => doRunSaved(s)} title={s.request.query}>{s.pinned ? '📌 ' : ''}{s.name}</button>
+                  {s.watch && (alerts[s.id]?.length ?? 0) > 0 && (
+                    <button className="chip-link badge" onClick={() => doShowAlerts(s)} title="查看新匹配">{alerts[s.id].length} 新</button>
+                  )}
+                  <button className="chip-link" onClick={() => doWatchSaved(s)} title={s.watch ? '取消关注' : '关注（新匹配时提醒）'}>{s.watch ? '🔔' : '🔕'}</button>
+                  <button className="chip-link" onClick={() => doPinSaved(s)} title={s.pinned ? '取消置顶' : '置顶'}>{s.pinned ? '↓' : '↑'}</button>
+                  <button className="chip-link" onClick={() => doRenameSaved(s)} title="重命名">✎</button>
+                  <button className="chip-link" onClick={() => doDeleteSaved(s)} title="删除">×</button>
+                </span>
+              ))}
+            </div>
+          )}
+          {facets && total > 0 && (
+            <div className="facets">
+              {facetTime && <button className="chip active" onClick={() => setFacetTime(null)}>{facetTime.label} ×</button>}
+              {facets.ext.slice(0, 8).map(f => (
+                <button key={`ext-${f.value}`} className="chip" onClick={() => setExtFilter(f.value)}>{f.value || '无扩展名'} {f.count}</button>
+              ))}
+              {facets.size.map(f => (
+                <button key={`size-${f.value}`} className="chip" onClick={() => { setMinSize(String((f.from ?? 0) / 1048576)); setMaxSize(f.to != null ? String((f.to - 1) / 1048576) : '') }}>{f.value} {f.count}</button>
+              ))}
+              {facets.year.slice(0, 6).map(f => (
+                <button key={`year-${f.value}`} className="chip" onClick={() => f.from != null && f.to != null && setFacetTime({ from: f.from, to: f.to, label: f.value })}>{f.value} 年 {f.count}</button>
+              ))}
+              {facets.dir.slice(0, 8).map(f => (
+                <button key={`dir-${f.value}`} className="chip" title={f.value} onClick={() => setPathPrefix(f.value)}>{shortenPath(f.value, 32)} {f.count}</button>
+              ))}
+            </div>
+          )}
           <div className="split">
             <div className="primary">
             {viewMode === 'table' ? (
@@ -517,11 +841,13 @@ export default function App() {
                   </tr>
                 </thead>
                 <tbody>
-                  {sortedResults.map((r, i) => (
+                  {results.map((r, i) => (
                     <tr key={i} className={selected?.path === r.path ? 'selected' : ''} onClick={() => setSelected(r)}>
                       <td className="name">
-                        <div>{highlight(r.name, query)}</div>
-                        {r.summary && <div className="muted" style={{ fontSize: 12, marginTop: 4 }}>{highlight(r.summary, query)}</div>}
+                        <div>{highlightRanges(r.name, r.name_highlights, query)}</div>
+                        {r.snippet
+                          ? <div className="muted" style={{ fontSize: 12, marginTop: 4 }}>…{highlightRanges(r.snippet, r.snippet_highlights, query)}…</div>
+                          : r.summary && <div className="muted" style={{ fontSize: 12, marginTop: 4 }}>{highlight(r.summary, query)}</div>}
                       </td>
                       <td className="path" title={r.path}>{shortenPath(r.path, pathMaxLen)}</td>
                       <td>{r.ext}</td>
@@ -532,22 +858,39 @@ export default function App() {
                   ))}
                   {results.length === 0 && (
                     <tr>
-                      <td colSpan={6} className="empty">暂无结果，试试输入关键词并点击搜索</td>
+                      <td colSpan={6} className="empty">
+                        {didYouMean
+                          ? <>暂无结果。您是不是要找：<button className="chip-link" style={{ color: 'var(--primary)' }} onClick={() => setQuery(didYouMean)}>{didYouMean}</button></>
+                          : '暂无结果，试试输入关键词并点击搜索'}
+                      </td>
                     </tr>
                   )}
                 </tbody>
               </table>
             ) : (
               <div className="cards">
-                {sortedResults.map((r, i) => (
+                {results.map((r, i) => (
                   <div key={i} className={`card ${selected?.path === r.path ? 'selected' : ''}`} onClick={() => setSelected(r)}>
-                    <div className="card-title">{highlight(r.name, query)}<span className="badge">{r.ext}</span></div>
-          {r.summary && <div className="card-path">{highlight(r.summary, query)}</div>}
+                    <div className="card-title">{highlightRanges(r.name, r.name_highlights, query)}<span className="badge">{r.kind === 'dir' ? '文件夹' : r.ext}</span>{r.collection && <span className="badge">{r.collection}</span>}</div>
+          {r.snippet
+            ? <div className="card-path">…{highlightRanges(r.snippet, r.snippet_highlights, query)}…</div>
+            : r.summary && <div className="card-path">{highlight(r.summary, query)}</div>}
           <div className="card-path" title={r.path}>{shortenPath(r.path, pathMaxLen)}</div>
               <div className="card-score">分数 {r.score.toFixed(4)} · 大小 {r.size != null ? formatBytes(r.size) : '-'} · 时间 {showRelativeTime ? formatRelativeTs(r.modified_ts) : formatTs(r.modified_ts)}</div>
                   </div>
                 ))}
-                {results.length === 0 && <div className="empty">暂无结果，试试输入关键词并点击搜索</div>}
+                {results.length === 0 && (
+                  <div className="empty">
+                    {didYouMean
+                      ? <>暂无结果。您是不是要找：<button className="chip-link" style={{ color: 'var(--primary)' }} onClick={() => setQuery(didYouMean)}>{didYouMean}</button></>
+                      : '暂无结果，试试输入关键词并点击搜索'}
+                  </div>
+                )}
+              </div>
+            )}
+            {results.length > 0 && results.length < total && (
+              <div className="row" style={{ justifyContent: 'center', marginTop: 12 }}>
+                <button className="ghost" onClick={loadMore} disabled={busy}>加载更多（{results.length}/{total}）</button>
               </div>
             )}
             </div>
@@ -559,15 +902,26 @@ export default function App() {
                   <div>类型：{selected.ext}</div>
           <div className="muted" title={selected.path}>路径：{shortenPath(selected.path, pathMaxLen)}</div>
                   <div className="muted">分数：{selected.score.toFixed(4)}</div>
+                  {selected.open_count != null && (
+                    <div className="muted">已打开 {selected.open_count} 次，最近：{formatRelativeTs(selected.last_opened_ts)}</div>
+                  )}
+                  {selected.explanation && (
+                    <div className="muted" title={JSON.stringify(selected.explanation.tree, null, 2)}>
+                      命中：{selected.explanation.fields.map(f => `${f.field} ${f.score.toFixed(3)}${f.boost !== 1 ? `（×${f.boost}）` : ''}`).join('，') || '-'}
+                    </div>
+                  )}
+                  {explain && analyzedTerms && (
+                    <div className="muted">查询词：{analyzedTerms.map(t => `${t.field}:${t.term}`).join(' ')}</div>
+                  )}
               <div className="muted">大小：{selected?.size != null ? formatBytes(selected.size) : '-'}</div>
               <div className="muted">时间：{showRelativeTime ? formatRelativeTs(selected?.modified_ts) : formatTs(selected?.modified_ts)}</div>
                 </div>
                 <div className="detail-actions">
-                  <button className="ghost" onClick={() => { if (inTauri && selected) openLocation(selected.path) }}>打开位置</button>
+                  <button className="ghost" onClick={() => { if (inTauri && selected) openLocation(selected.path, selected.raw_path) }}>打开位置</button>
                   <button className="ghost" onClick={() => { if (selected) { navigator.clipboard?.writeText?.(selected.path).then(() => setMsg('已复制路径')).catch(() => setMsg('复制失败')) } }}>复制路径</button>
                   <button className="ghost">收藏</button>
                 </div>
-                <div className="preview muted">{selected.summary ? highlight(selected.summary, query) : '预览占位（文本片段/缩略图）'}</div>
+                <div className="preview muted">{selected.snippet ? highlightRanges(selected.snippet, selected.snippet_highlights, query) : (selected.summary ? highlight(selected.summary, query) : '预览占位（文本片段/缩略图）')}</div>
               </div>
             ) : (
               <div className="empty">请选择左侧结果查看详情</div>
@@ -673,7 +1027,10 @@ export default function App() {
             </label>
             <button className="ghost" onClick={doSaveConfig} disabled={busy || !inTauri}>保存为默认配置</button>
             <button className="ghost" onClick={doResetConfig} disabled={busy || !inTauri}>恢复默认配置</button>
+            <button className="ghost" onClick={() => { if (inTauri) clearOpenHistory().then(() => setMsg('已清空打开历史')).catch((e) => logError(e, 'clear_open_history')) }} disabled={busy || !inTauri}>清空打开历史</button>
             <button className="ghost" onClick={() => { if (inTauri) startAutoScanNow().catch((e) => logError(e, 'start_auto_scan_now')) }} disabled={busy || !inTauri}>立即自动扫描</button>
+            <button className="ghost" onClick={() => { if (inTauri) cancelScan(['manual', indexDir]).then((n) => setMsg(n > 0 ? '已取消扫描' : '当前没有正在运行的扫描')).catch((e) => logError(e, 'cancel_scan')) }} disabled={!inTauri}>取消扫描</button>
+            <button className="ghost" onClick={() => { if (inTauri) cancelScan().then((n) => setMsg(n > 0 ? `已停止全部 ${n} 个扫描` : '当前没有正在运行的扫描')).catch((e) => logError(e, 'cancel_scan')) }} disabled={!inTauri}>停止全部扫描</button>
           </div>
           {idxTotal > 0 && (
             <div style={{ marginTop: 12 }}>
@@ -704,6 +1061,9 @@ export default function App() {
                 <div className="card-path">索引目录：{diag.index_dir}</div>
                 <div className="card-score">索引可打开：{diag.index_open_ok ? '是' : '否'} · 文档数：{diag.index_doc_count ?? '-'} · 字段：{diag.schema_fields?.length ?? 0}</div>
                 <div className="card-score">扫描根数：{diag.config_scan_roots_count} · 自动扫描：{diag.config_auto_scan_enabled ? '已启用' : '未启用'}</div>
+                {diag.last_scan ? (
+                  <div className="card-score">最近扫描：{diag.last_scan.files} 个文件 · 忽略规则：{diag.last_scan.respect_ignore_files ? `.searcheveryignore ${diag.last_scan.ignored.searcheveryignore} / .ignore ${diag.last_scan.ignored.ignore} / .gitignore ${diag.last_scan.ignored.gitignore} / git exclude ${diag.last_scan.ignored.git_exclude} / 全局 ${diag.last_scan.ignored.git_global}` : '未启用'} · 非 UTF-8 路径：{diag.last_scan.non_utf8_paths}{diag.last_scan.cancelled ? ' · 已取消' : ''}</div>
+                ) : null}
                 <div className="card-score">管道：{diag.pipeline_started ? '进行中' : '未进行'} · 已完成：{diag.pipeline_completed ? '是' : '否'} · 上次日期：{diag.pipeline_last_day ?? '-'}</div>
                 <div className="card-score">系统：CPU 平均 {diag.sys_cpu_avg != null ? `${diag.sys_cpu_avg.toFixed(1)}%` : '-'} · 内存 总 {diag.sys_total_mem_kib != null ? `${Math.round(diag.sys_total_mem_kib / 1024)} MiB` : '-'} / 可用 {diag.sys_free_mem_kib != null ? `${Math.round(diag.sys_free_mem_kib / 1024)} MiB` : '-'}</div>
                 {diag.warnings.length > 0 ? (
@@ -732,9 +1092,9 @@ export default function App() {
       setBusy(true)
       setMsg('扫描中...')
       try {
-        let paths: string[] = []
+        let files: FileMeta[] = []
         if (scanned.length > 0) {
-          paths = scanned.map(f => f.path)
+          files = scanned
         } else {
           const opts = {
             roots: parseCSV(roots),
@@ -755,10 +1115,11 @@ export default function App() {
           const res = await scanPathsProgress(opts)
           unlisten()
           setScanned(res)
-          paths = res.map(f => f.path)
+          files = res
         }
+        files = files.filter(f => f.kind !== 'dir')
         setMsg('正在分析重复...')
-        const groups = await detectDuplicates(paths)
+        const groups = await detectDuplicates(files.map(f => f.path), files.map(f => f.raw_path ?? null))
         setDupGroups(groups)
         setMsg(`检测完成：发现 ${groups.length} 组重复`)
       } catch (e: any) {
@@ -773,15 +1134,18 @@ export default function App() {
       return dupGroups.filter(g => (dupIncludeHash && g.kind === 'hash') || (dupIncludeName && g.kind === 'name'))
     }
 
-    async function handleDelete(path: string) {
+    async function handleDelete(path: string, rawPath?: string | null) {
       if (!inTauri) return
       if (!confirm(`确认删除该文件并移除索引记录？\n${path}`)) return
       setBusy(true)
       setMsg('删除中...')
       try {
-        await deleteFileAndIndex(path, indexDir)
-        // 从 UI 中移除该文件
-        setDupGroups(prev => prev.map(g => ({ ...g, files: g.files.filter(p => p !== path) })).filter(g => g.files.length > 1))
+        await deleteFileAndIndex(path, indexDir, rawPath)
+        // 从 UI 中移除该文件（files 与 raw_paths 按下标对应，需同步过滤）
+        setDupGroups(prev => prev.map(g => {
+          const keep = g.files.map((p, i) => !(p === path && (g.raw_paths?.[i] ?? null) === (rawPath ?? null)))
+          return { ...g, files: g.files.filter((_, i) => keep[i]), raw_paths: g.raw_paths?.filter((_, i) => keep[i]) }
+        }).filter(g => g.files.length > 1))
         setMsg('已删除并移除索引记录')
       } catch (e: any) {
         logError(e, 'delete_file_and_index', { path, indexDir })
@@ -820,8 +1184,8 @@ export default function App() {
                       <div key={idx} className="row" style={{ justifyContent: 'space-between' }}>
                         <span className="card-path" title={p}>{shortenPath(p, pathMaxLen)}</span>
                         <div className="detail-actions">
-                          <button className="ghost" onClick={() => openLocation(p)}>定位</button>
-                          <button className="ghost" onClick={() => handleDelete(p)} style={{ color: 'crimson' }}>删除</button>
+                          <button className="ghost" onClick={() => openLocation(p, g.raw_paths?.[idx])}>定位</button>
+                          <button className="ghost" onClick={() => handleDelete(p, g.raw_paths?.[idx])} style={{ color: 'crimson' }}>删除</button>
                         </div>
                       </div>
                     ))}
@@ -877,6 +1241,36 @@ export default function App() {
               <input type="checkbox" checked={autoScanEnabled} onChange={e => setAutoScanEnabled(e.target.checked)} />
               启用每日自动扫描（系统空闲时）
             </label>
+            <label className="checkbox" style={{ marginTop: 8 }}>
+              <input type="checkbox" checked={respectIgnoreFiles} onChange={e => setRespectIgnoreFiles(e.target.checked)} />
+              扫描时遵循 .gitignore / .ignore / .searcheveryignore
+            </label>
+          </div>
+          <div className="row" style={{ flexDirection: 'column', alignItems: 'stretch', gap: 8, marginTop: 16 }}>
+            <div className="status">用户词典与同义词</div>
+            <div className="muted" style={{ fontSize: 12 }}>用户词加入分词词典（切分变化的词所在的文档会按新词典重写）；同义词在检索时展开，任一成员命中即可，短语检索不展开</div>
+            <div className="row" style={{ gap: 6, flexWrap: 'wrap' }}>
+              {(dictionary?.words ?? []).map(w => (
+                <span key={w.word} className="chip">
+                  {w.word}{w.tag ? ` /${w.tag}` : ''}
+                  <button className="chip-link" onClick={() => doRemoveUserWord(w.word)} disabled={busy} title="删除">×</button>
+                </span>
+              ))}
+              {dictionary && dictionary.words.length === 0 && <span className="muted">暂无用户词</span>}
+            </div>
+            <textarea value={dictText} onChange={e => setDictText(e.target.value)} rows={5} placeholder={'jieba 用户词典格式，每行：词 [词频] [词性]\n深度求索 10 nz\n向量检索'}
+              style={{ padding: '8px 10px', border: '1px solid var(--border)', borderRadius: 8, background: 'transparent', color: 'var(--text)', fontFamily: 'monospace' }} />
+            <div className="row" style={{ gap: 8 }}>
+              <button className="ghost" onClick={() => doImportDictionary(false)} disabled={busy || !inTauri || !dictText.trim()}>导入（追加）</button>
+              <button className="ghost" onClick={() => doImportDictionary(true)} disabled={busy || !inTauri || !dictText.trim()}>导入（替换）</button>
+              <button className="ghost" onClick={doExportDictionary} disabled={busy || !inTauri}>导出</button>
+            </div>
+            <textarea value={synonymText} onChange={e => setSynonymText(e.target.value)} rows={4} placeholder={'每行一组同义词，逗号分隔，如：合同, 协议, contract'}
+              style={{ padding: '8px 10px', border: '1px solid var(--border)', borderRadius: 8, background: 'transparent', color: 'var(--text)' }} />
+            <div className="row" style={{ gap: 8 }}>
+              <button className="ghost" onClick={doSaveSynonyms} disabled={busy || !inTauri}>保存同义词</button>
+              <span className="muted">{dictMsg}</span>
+            </div>
           </div>
         </div>
       </div>
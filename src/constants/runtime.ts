@@ -13,9 +13,16 @@ export const INVOKE_TIMEOUTS = {
   buildIndex: 300000,
   searchQuery: 60000,
   openLocation: 30000,
+  // 检索历史与已保存的搜索（读写配置目录下的小文件）
+  searchHistory: 30000,
+  // 用户词变更可能重写受影响的文档
+  dictionary: 300000,
+  // 输入建议（逐键请求，超时即放弃）
+  suggest: 5000,
   // 新增：合并管道启动与手动自动扫描触发的超时
   pipelineStart: 600000, // 10 分钟，管道可能较长
   startAutoScanNow: 60000,
+  cancelScan: 30000,
   // 诊断报告
   diagnosticsReport: 45000,
 }
\ No newline at end of file
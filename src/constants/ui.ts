@@ -1,3 +1,5 @@
 // UI 展示相关常量（支持通过环境变量覆盖）
 const envLen = Number((import.meta as any).env?.VITE_PATH_MAX_LEN ?? (globalThis as any).VITE_PATH_MAX_LEN)
-export const PATH_MAX_LEN = Number.isFinite(envLen) && envLen > 10 ? envLen : 80
\ No newline at end of file
+export const PATH_MAX_LEN = Number.isFinite(envLen) && envLen > 10 ? envLen : 80
+// 检索结果每页条数
+export const PAGE_SIZE = 50
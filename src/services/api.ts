@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/tauri'
-import type { AppConfig, FileMeta, SearchResult, SearchRequest, DiagnosticsReport, DupGroup } from '../types'
+import type { AppConfig, FileMeta, SearchRequest, SearchResponse, DiagnosticsReport, DupGroup, RustQueryRequest, SearchHistoryEntry, SavedSearch, SearchAlerts, SuggestResponse, Dictionary, DictionaryUpdate, UserWord } from '../types'
 import { INVOKE_DEFAULTS, INVOKE_TIMEOUTS } from '../constants/runtime'
 
 type InvokeOptions = { timeoutMs?: number; retries?: number }
@@ -44,6 +44,9 @@ export async function scanPaths(opts: {
   exclude_patterns: string[]
   max_file_size_mb: number
   follow_symlinks: boolean
+  respect_ignore_files?: boolean
+  one_file_system?: boolean
+  skip_mount_kinds?: string[]
 }): Promise<FileMeta[]> {
   return safeInvoke<FileMeta[]>('scan_paths', { opts }, { timeoutMs: INVOKE_TIMEOUTS.scanPaths })
 }
@@ -54,10 +57,19 @@ export async function scanPathsProgress(opts: {
   exclude_patterns: string[]
   max_file_size_mb: number
   follow_symlinks: boolean
+  respect_ignore_files?: boolean
+  one_file_system?: boolean
+  skip_mount_kinds?: string[]
 }): Promise<FileMeta[]> {
   return safeInvoke<FileMeta[]>('scan_paths_progress', { opts }, { timeoutMs: INVOKE_TIMEOUTS.scanPaths })
 }
 
+// 取消正在运行的扫描，返回被通知的扫描数量。targets 为扫描 id、集合名称、索引目录或 'manual'（手动扫描）；
+// 省略时停止全部扫描（包括其他集合的调度扫描）
+export async function cancelScan(targets?: string[]): Promise<number> {
+  return safeInvoke<number>('cancel_scan', { targets: targets ?? null }, { timeoutMs: INVOKE_TIMEOUTS.cancelScan })
+}
+
 // 构建索引
 export async function buildInvertedIndex(files: FileMeta[], opts: { indexDir: string; enable_content_parse: boolean }): Promise<void> {
   // 后端期望字段为 snake_case：index_dir、enable_content_parse
@@ -71,48 +83,199 @@ export async function buildInvertedIndexProgress(files: FileMeta[], opts: { inde
 }
 
 // 合并：扫描并索引（后台事件驱动）
-export async function scanAndIndexPipeline(opts: { roots: string[]; exclude_patterns: string[] }, indexOpts: { indexDir: string; enable_content_parse: boolean }): Promise<void> {
+export async function scanAndIndexPipeline(opts: { roots: string[]; exclude_patterns: string[]; respect_ignore_files?: boolean; one_file_system?: boolean; skip_mount_kinds?: string[] }, indexOpts: { indexDir: string; enable_content_parse: boolean }): Promise<void> {
   const rustIndexOpts = { index_dir: indexOpts.indexDir, enable_content_parse: indexOpts.enable_content_parse }
   // 为兼容可能的参数命名差异，同时发送 index_opts 与 indexOpts
-  const payload = { opts: { roots: opts.roots, exclude_patterns: opts.exclude_patterns, max_file_size_mb: 500, follow_symlinks: false }, index_opts: rustIndexOpts, indexOpts: rustIndexOpts }
+  const payload = { opts: { roots: opts.roots, exclude_patterns: opts.exclude_patterns, max_file_size_mb: 500, follow_symlinks: false, respect_ignore_files: opts.respect_ignore_files ?? false, one_file_system: opts.one_file_system ?? false, skip_mount_kinds: opts.skip_mount_kinds }, index_opts: rustIndexOpts, indexOpts: rustIndexOpts }
   // 可选调试：查看实际发送的负载结构
   // console.debug('scan_and_index_pipeline payload', payload)
   await safeInvoke('scan_and_index_pipeline', payload, { timeoutMs: INVOKE_TIMEOUTS.pipelineStart })
 }
 
-// 立即触发自动扫描（由后端根据系统空闲条件执行）
-export async function startAutoScanNow(): Promise<void> {
-  await safeInvoke('start_auto_scan_now', undefined, { timeoutMs: INVOKE_TIMEOUTS.startAutoScanNow })
+// 立即触发自动扫描（由后端根据系统空闲条件执行）；collection 为空时扫描主索引
+export async function startAutoScanNow(collection?: string): Promise<void> {
+  await safeInvoke('start_auto_scan_now', { collection: collection ?? null }, { timeoutMs: INVOKE_TIMEOUTS.startAutoScanNow })
 }
 
-// 查询
-export async function searchQuery(req: SearchRequest): Promise<SearchResult[]> {
-  // Rust 端期望字段为 snake_case：index_dir；filters 的字段同样为 ext/min_size/max_size
+// 文件名即时匹配（子串/前缀），用于逐键检索
+export async function nameSearch(req: SearchRequest, matchMode: 'substring' | 'prefix' = 'substring'): Promise<SearchResponse> {
   const rustReq = {
     query: req.query,
+    index_dir: req.indexDir,
+    mode: matchMode,
     filters: req.filters ? {
       ext: req.filters.ext,
       min_size: req.filters.min_size,
       max_size: req.filters.max_size,
+      kind: req.filters.kind,
+      time_range: req.filters.time_range,
+      path_prefix: req.filters.path_prefix,
+    } : null,
+    offset: req.offset ?? 0,
+    limit: req.limit,
+  }
+  return safeInvoke<SearchResponse>('name_search', { req: rustReq }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery })
+}
+
+// Rust 端期望字段为 snake_case：index_dir；filters 的字段同样为 ext/min_size/max_size
+function toRustQuery(req: SearchRequest): RustQueryRequest {
+  return {
+    query: req.query,
+    filters: req.filters ? {
+      ext: req.filters.ext,
+      min_size: req.filters.min_size,
+      max_size: req.filters.max_size,
+      kind: req.filters.kind,
+      time_range: req.filters.time_range,
+      path_prefix: req.filters.path_prefix,
     } : null,
     index_dir: req.indexDir,
+    offset: req.offset ?? 0,
+    limit: req.limit,
+    sort_by: req.sortBy,
+    sort_order: req.sortOrder,
+    fuzzy: req.fuzzy ?? false,
+    fuzzy_distance: req.fuzzyDistance,
+    fuzzy_prefix_len: req.fuzzyPrefixLen,
+    regex: req.regex ?? false,
+    regex_target: req.regexTarget,
+    explain: req.explain ?? false,
+    facets: req.facets ?? false,
+    collections: req.collections ?? [],
+    recent: req.recent ?? false,
+  }
+}
+
+// 已保存搜索中的请求（snake_case）还原为前端请求，用于重新执行后继续分页
+export function fromRustQuery(r: RustQueryRequest): SearchRequest {
+  return {
+    query: r.query,
+    filters: r.filters,
+    indexDir: r.index_dir,
+    offset: r.offset,
+    limit: r.limit,
+    sortBy: r.sort_by,
+    sortOrder: r.sort_order,
+    fuzzy: r.fuzzy,
+    fuzzyDistance: r.fuzzy_distance,
+    fuzzyPrefixLen: r.fuzzy_prefix_len,
+    regex: r.regex,
+    regexTarget: r.regex_target,
+    explain: r.explain,
+    facets: r.facets,
+    collections: r.collections,
+    recent: r.recent,
   }
-  return safeInvoke<SearchResult[]>('search_query', { req: rustReq }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery, retries: 1 })
+}
+
+// 查询
+export async function searchQuery(req: SearchRequest): Promise<SearchResponse> {
+  return safeInvoke<SearchResponse>('search_query', { req: toRustQuery(req) }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery, retries: 1 })
+}
+
+// 输入建议：最近的检索与词典补全；correct 为 true 时同时返回拼写纠正
+export async function suggest(query: string, indexDir: string, correct = false, limit?: number): Promise<SuggestResponse> {
+  return safeInvoke<SuggestResponse>('suggest', { req: { query, index_dir: indexDir, limit: limit ?? null, correct } }, { timeoutMs: INVOKE_TIMEOUTS.suggest })
+}
+
+// 检索历史（最新在前）
+export async function listSearchHistory(): Promise<SearchHistoryEntry[]> {
+  return safeInvoke<SearchHistoryEntry[]>('list_search_history', undefined, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function clearSearchHistory(): Promise<void> {
+  await safeInvoke('clear_search_history', undefined, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+// 已保存的搜索：置顶的在前，其余按 order
+export async function listSavedSearches(): Promise<SavedSearch[]> {
+  return safeInvoke<SavedSearch[]>('list_saved_searches', undefined, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function saveSearch(name: string, req: SearchRequest): Promise<SavedSearch> {
+  return safeInvoke<SavedSearch>('save_search', { name, req: toRustQuery(req) }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function runSavedSearch(id: number): Promise<SearchResponse> {
+  return safeInvoke<SearchResponse>('run_saved_search', { id }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery })
+}
+
+export async function renameSavedSearch(id: number, name: string): Promise<void> {
+  await safeInvoke('rename_saved_search', { id, name }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function deleteSavedSearch(id: number): Promise<void> {
+  await safeInvoke('delete_saved_search', { id }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function pinSavedSearch(id: number, pinned: boolean): Promise<void> {
+  await safeInvoke('pin_saved_search', { id, pinned }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+// 关注：开启时以当前匹配为基线，之后索引提交时提醒新出现的匹配
+export async function watchSavedSearch(id: number, watch: boolean): Promise<SavedSearch> {
+  return safeInvoke<SavedSearch>('watch_saved_search', { id, watch }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery })
+}
+
+export async function listSearchAlerts(): Promise<SearchAlerts[]> {
+  return safeInvoke<SearchAlerts[]>('list_search_alerts', undefined, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+// 标记已读：paths 省略时清空该搜索的全部未读
+export async function markSearchAlertsRead(id: number, paths?: string[]): Promise<void> {
+  await safeInvoke('mark_search_alerts_read', { id, paths: paths ?? null }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function reorderSavedSearches(ids: number[]): Promise<void> {
+  await safeInvoke('reorder_saved_searches', { ids }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
 }
 
 // 打开文件所在位置
-export async function openLocation(path: string): Promise<void> {
-  await safeInvoke('open_location', { path }, { timeoutMs: INVOKE_TIMEOUTS.openLocation })
+export async function openLocation(path: string, rawPath?: string | null): Promise<void> {
+  // rawPath：非 UTF-8 路径的原始字节（十六进制），后端以其为准还原真实路径
+  await safeInvoke('open_location', { path, rawPath: rawPath ?? null }, { timeoutMs: INVOKE_TIMEOUTS.openLocation })
+}
+
+// 清空打开历史（常用文件加权排序与最近打开模式的数据来源）
+export async function clearOpenHistory(): Promise<void> {
+  await safeInvoke('clear_open_history', undefined, { timeoutMs: INVOKE_TIMEOUTS.openLocation })
+}
+
+// 用户词典与同义词
+export async function getDictionary(): Promise<Dictionary> {
+  return safeInvoke<Dictionary>('get_dictionary', undefined, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+// 替换全部同义词组，返回保存的组数
+export async function setSynonyms(groups: string[][]): Promise<number> {
+  return safeInvoke<number>('set_synonyms', { groups }, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
+}
+
+export async function addUserWords(words: UserWord[]): Promise<DictionaryUpdate> {
+  return safeInvoke<DictionaryUpdate>('add_user_words', { words }, { timeoutMs: INVOKE_TIMEOUTS.dictionary })
+}
+
+export async function removeUserWords(words: string[]): Promise<DictionaryUpdate> {
+  return safeInvoke<DictionaryUpdate>('remove_user_words', { words }, { timeoutMs: INVOKE_TIMEOUTS.dictionary })
+}
+
+// 导入 jieba 用户词典格式文本（每行 "词 词频 词性"）；replace 为 true 时替换现有的用户词
+export async function importUserDictionary(text: string, replace = false): Promise<DictionaryUpdate> {
+  return safeInvoke<DictionaryUpdate>('import_user_dictionary', { text, replace }, { timeoutMs: INVOKE_TIMEOUTS.dictionary })
+}
+
+export async function exportUserDictionary(): Promise<string> {
+  return safeInvoke<string>('export_user_dictionary', undefined, { timeoutMs: INVOKE_TIMEOUTS.searchHistory })
 }
 
 // 检测重复文件（按内容哈希与文件名）
-export async function detectDuplicates(paths: string[]): Promise<DupGroup[]> {
-  return safeInvoke<DupGroup[]>('detect_duplicates', { paths }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery })
+export async function detectDuplicates(paths: string[], rawPaths?: (string | null)[]): Promise<DupGroup[]> {
+  return safeInvoke<DupGroup[]>('detect_duplicates', { paths, rawPaths: rawPaths ?? null }, { timeoutMs: INVOKE_TIMEOUTS.searchQuery })
 }
 
 // 删除重复文件：删除磁盘文件并移除索引记录
-export async function deleteFileAndIndex(path: string, indexDir: string): Promise<void> {
-  await safeInvoke('delete_file_and_index', { path, index_dir: indexDir, indexDir }, { timeoutMs: INVOKE_TIMEOUTS.buildIndex })
+export async function deleteFileAndIndex(path: string, indexDir: string, rawPath?: string | null): Promise<void> {
+  await safeInvoke('delete_file_and_index', { path, index_dir: indexDir, indexDir, rawPath: rawPath ?? null }, { timeoutMs: INVOKE_TIMEOUTS.buildIndex })
 }
 
 // 诊断报告
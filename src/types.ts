@@ -4,6 +4,11 @@ export type FileMeta = {
   ext: string
   size: number
   modified_ts: number
+  // 条目类型；文件夹的 size 为其下文件总大小
+  kind?: 'file' | 'dir' | 'symlink'
+  child_count?: number
+  // 非 UTF-8 路径的原始字节（十六进制）；path 为有损展示值
+  raw_path?: string
 }
 
 export type SearchResult = {
@@ -14,6 +19,27 @@ export type SearchResult = {
   size?: number
   modified_ts?: number
   summary?: string
+  kind: 'file' | 'dir' | 'symlink'
+  child_count?: number
+  raw_path?: string
+  // 后端生成的命中片段与高亮区间（[start, end)，UTF-16 偏移）
+  snippet?: string
+  snippet_highlights?: [number, number][]
+  name_highlights?: [number, number][]
+  // 仅 explain 模式：名称与正文的 BM25 得分、命中字段与得分明细
+  bm25?: number
+  hit_fields?: string[]
+  explanation?: ScoreExplanation
+  // 多集合检索时结果所属的索引集合
+  collection?: string
+  // 打开历史：通过本应用打开的次数与最近一次打开时间（Unix 秒）
+  open_count?: number
+  last_opened_ts?: number
+}
+
+export type ScoreExplanation = {
+  fields: { field: string; score: number; boost: number }[]
+  tree: unknown
 }
 
 export type AppConfig = {
@@ -25,6 +51,26 @@ export type AppConfig = {
   path_max_len?: number
   // 是否启用每日自动扫描（系统空闲时）
   auto_scan_enabled?: boolean
+  // 主索引的自动扫描计划：manual | hourly | daily
+  scan_schedule?: 'manual' | 'hourly' | 'daily'
+  // 扫描时遵循 .gitignore / .ignore / .searcheveryignore
+  respect_ignore_files?: boolean
+  // 扫描不跨越挂载点
+  one_file_system?: boolean
+  // 跳过的挂载分类（Linux）：pseudo / tmpfs / overlay / fuse / network
+  skip_mount_kinds?: string[]
+  // 其他命名索引集合（各自的扫描目录与计划）；主索引为 "default"
+  collections?: IndexCollection[]
+}
+
+export type IndexCollection = {
+  name: string
+  index_dir: string
+  scan_roots: string[]
+  exclude_patterns: string[]
+  // 自动扫描计划：manual | hourly | daily
+  schedule?: 'manual' | 'hourly' | 'daily'
+  enabled?: boolean
 }
 
 export type PageKey = 'search' | 'index' | 'dup' | 'settings' | 'about'
@@ -36,6 +82,8 @@ export type DupGroup = {
   kind: 'hash' | 'name'
   key: string // 当 kind=hash 为内容哈希；当 kind=name 为文件名
   files: string[]
+  // 与 files 一一对应：非 UTF-8 路径的原始字节（十六进制）
+  raw_paths?: (string | null)[]
 }
 
 // Tauri 事件：索引进度
@@ -50,6 +98,8 @@ export type IndexProgressPayload = {
 // 自动扫描启动事件载荷
 export type AutoScanStartPayload = {
   reason?: string
+  collection?: string
+  schedule?: string
 }
 
 // 前端查询过滤与请求类型
@@ -57,12 +107,128 @@ export type SearchFilters = {
   ext?: string[]
   min_size?: number
   max_size?: number
+  kind?: 'file' | 'dir'
+  // 修改时间区间 [起, 止]（Unix 秒，含两端），null 表示不限
+  time_range?: [number | null, number | null]
+  // 仅检索该目录（含子目录）下的条目
+  path_prefix?: string
 }
 
 export type SearchRequest = {
   query: string
   filters: SearchFilters | null
   indexDir: string
+  offset?: number
+  limit?: number
+  // 排序：score（默认）| modified | size | name | path；相同时按相关度
+  sortBy?: 'score' | 'modified' | 'size' | 'name' | 'path'
+  sortOrder?: 'asc' | 'desc'
+  // 文件名容错检索（编辑距离 1~2，可要求前缀一致）
+  fuzzy?: boolean
+  fuzzyDistance?: number
+  fuzzyPrefixLen?: number
+  // 正则模式：匹配文件名或完整路径
+  regex?: boolean
+  regexTarget?: 'name' | 'path'
+  // 排序解释：返回命中字段与各来源得分（调试用）
+  explain?: boolean
+  // 分面统计（扩展名、大小区间、修改年月、目录）
+  facets?: boolean
+  // 多集合检索：按名称选择索引集合（非空时忽略 indexDir），结果得分按集合归一化后合并
+  collections?: string[]
+  // 最近打开模式：仅检索打开历史中的条目，按最近打开时间排序
+  recent?: boolean
+}
+
+// 发往后端的检索请求（snake_case，与 Rust 端 QueryRequest 一致）；已保存的搜索以此形式存储
+export type RustQueryRequest = {
+  query: string
+  filters: SearchFilters | null
+  index_dir: string
+  offset: number
+  limit?: number
+  sort_by?: SearchRequest['sortBy']
+  sort_order?: SearchRequest['sortOrder']
+  fuzzy: boolean
+  fuzzy_distance?: number
+  fuzzy_prefix_len?: number
+  regex: boolean
+  regex_target?: SearchRequest['regexTarget']
+  explain: boolean
+  facets: boolean
+  collections: string[]
+  recent: boolean
+}
+
+// 检索历史条目（ts 为 Unix 秒，total 为当时的命中数）
+export type SearchHistoryEntry = {
+  query: string
+  filters?: SearchFilters | null
+  ts: number
+  total: number
+}
+
+// 已保存的搜索：置顶的在前，其余按 order 升序
+export type SavedSearch = {
+  id: number
+  name: string
+  request: RustQueryRequest
+  pinned: boolean
+  order: number
+  created_ts: number
+  last_run_ts?: number
+  // 关注：索引提交后出现新匹配时提醒
+  watch?: boolean
+}
+
+// 关注提醒：新出现的匹配（ts 为发现时间，Unix 秒）
+export type AlertMatch = { path: string; name: string; ts: number }
+
+export type SearchAlerts = { id: number; name: string; unread: AlertMatch[] }
+
+// Tauri 事件 saved_search_alert 的载荷
+export type SavedSearchAlertPayload = { id: number; name: string; matches: AlertMatch[]; unread_count: number }
+
+// 检索响应：total 为命中总数，results 为当前页
+export type SearchResponse = {
+  total: number
+  elapsed_ms: number
+  offset: number
+  limit: number
+  fuzzy_applied: boolean
+  timed_out: boolean
+  results: SearchResult[]
+  analyzed_terms?: { field: string; term: string }[]
+  facets?: Facets
+  // 无结果时按词典给出的拼写纠正
+  did_you_mean?: string
+}
+
+// 输入建议：kind 为 history（最近的检索）或 term（词典补全，附文档频率）
+export type Suggestion = { text: string; kind: 'history' | 'term'; doc_freq?: number }
+
+export type SuggestResponse = { suggestions: Suggestion[]; did_you_mean?: string }
+
+// 用户词（jieba 用户词典的一行：词 词频 词性）
+export type UserWord = { word: string; freq?: number | null; tag?: string | null; split?: string[] | null }
+
+// 用户词典与同义词组（成员为小写）
+export type Dictionary = { words: UserWord[]; synonyms: string[][] }
+
+// 用户词变更结果：retokenized 为按新词典重写的文档数；pending 为正在写入、待扫描结束后重写的索引数
+export type DictionaryUpdate = { words: number; retokenized: number; pending?: number }
+
+// 分面计数；from/to 为数值区间 [from, to)（大小为字节，时间为 Unix 秒）
+export type FacetCount = { value: string; count: number; from?: number; to?: number }
+
+export type Facets = {
+  ext: FacetCount[]
+  size: FacetCount[]
+  year: FacetCount[]
+  month: FacetCount[]
+  // dir 为 dir_base 的下一级目录
+  dir_base: string
+  dir: FacetCount[]
 }
 
 // 后端诊断报告返回结构
@@ -79,5 +245,36 @@ export type DiagnosticsReport = {
   sys_cpu_avg?: number
   sys_total_mem_kib?: number
   sys_free_mem_kib?: number
+  config_respect_ignore_files: boolean
+  last_scan?: ScanStats
+  config_one_file_system: boolean
+  mounts: MountReport[]
   warnings: string[]
+}
+
+// 挂载点信息（仅 Linux）
+export type MountReport = {
+  mount_point: string
+  fs_type: string
+  source: string
+  kind: 'disk' | 'pseudo' | 'tmpfs' | 'overlay' | 'fuse' | 'network'
+  skipped: boolean
+}
+
+// 各忽略规则来源排除的路径数量
+export type IgnoreStats = {
+  searcheveryignore: number
+  ignore: number
+  gitignore: number
+  git_exclude: number
+  git_global: number
+}
+
+// 最近一次扫描统计
+export type ScanStats = {
+  files: number
+  respect_ignore_files: boolean
+  ignored: IgnoreStats
+  non_utf8_paths: number
+  cancelled: boolean
 }
\ No newline at end of file
@@ -53,4 +53,19 @@ export function highlight(text: string, q: string): JSX.Element {
       {parts.map((p, i) => (terms.some(t => p.toLowerCase() === t.toLowerCase()) ? <mark key={i}>{p}</mark> : <span key={i}>{p}</span>))}
     </span>
   )
-}
\ No newline at end of file
+}
+
+// 按后端返回的区间（[start, end)，UTF-16 偏移）高亮；无区间时退回按查询词匹配
+export function highlightRanges(text: string, ranges: [number, number][] | undefined, q: string): JSX.Element {
+  if (!ranges || ranges.length === 0) return highlight(text, q)
+  const parts: JSX.Element[] = []
+  let pos = 0
+  ranges.forEach(([start, end], i) => {
+    if (start < pos || end > text.length) return
+    if (start > pos) parts.push(<span key={`t${i}`}>{text.slice(pos, start)}</span>)
+    parts.push(<mark key={`m${i}`}>{text.slice(start, end)}</mark>)
+    pos = end
+  })
+  if (pos < text.length) parts.push(<span key="rest">{text.slice(pos)}</span>)
+  return <span>{parts}</span>
+}